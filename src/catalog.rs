@@ -0,0 +1,33 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::{read_toml, LoadError};
+use crate::model::{Module, Speciality, SupplementarySubject};
+
+/// Modules the programme offers, together with its specialities and supplementary subjects
+#[derive(Debug, Deserialize)]
+pub struct Catalog {
+    /// Specialities of the programme, master modules can only count toward these
+    pub specialities: Vec<Speciality>,
+    /// Subjects modules outside of computer science can belong to
+    #[serde(default)]
+    pub supplementary_subjects: Vec<SupplementarySubject>,
+    /// Modules of the catalog, written as `[[module]]` tables
+    #[serde(rename = "module", default)]
+    pub modules: Vec<Module>,
+}
+
+impl Catalog {
+    /// Reads a catalog from a TOML file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Catalog, LoadError> {
+        read_toml(path.as_ref())
+    }
+
+    /// Looks up a module by its identifier
+    pub fn find(&self, identifier: &str) -> Option<&Module> {
+        self.modules
+            .iter()
+            .find(|module| module.identifier == identifier)
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use studienplan::model::{ModuleType, Objective, SemesterType, Speciality};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Plan your studies with an SMT solver")]
+pub struct Cli {
+    /// Module catalog to choose modules from
+    #[arg(long, global = true, default_value = "catalog.toml")]
+    pub catalog: PathBuf,
+
+    /// Personal plan with the semester timeline and already taken modules
+    #[arg(long, global = true, default_value = "plan.toml")]
+    pub plan: PathBuf,
+
+    /// Examination regulation (SPO) the plan has to satisfy
+    #[arg(long, global = true, default_value = "regulation.toml")]
+    pub regulation: PathBuf,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Enumerate plans that satisfy all rules
+    Solve(SolveArgs),
+    /// Validate a fully specified plan against the rules
+    Check(SolverArgs),
+    /// Browse the module catalog
+    ListModules(ListModulesArgs),
+    /// Look for mistakes in the catalog and plan file
+    Validate,
+    /// Explain why no plan satisfies the rules
+    Explain(SolverArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct SolverArgs {
+    /// Give up after this many milliseconds
+    #[arg(long)]
+    pub timeout: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+pub struct SolveArgs {
+    #[command(flatten)]
+    pub solver: SolverArgs,
+
+    /// Stop after this many plans
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Only find the best plan by this objective, overrides the plan file
+    #[arg(long)]
+    pub objective: Option<Objective>,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct ListModulesArgs {
+    /// Only show modules counting toward this speciality
+    #[arg(long)]
+    pub speciality: Option<Speciality>,
+
+    /// Only show modules of this type
+    #[arg(long, value_enum)]
+    pub module_type: Option<ModuleKind>,
+
+    /// Only show modules offered in this semester type
+    #[arg(long)]
+    pub semester: Option<SemesterType>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ModuleKind {
+    Lecture,
+    Lab,
+    Seminar,
+    Thesis,
+    KeyQualification,
+}
+
+impl ModuleKind {
+    pub fn matches(&self, module_type: &ModuleType) -> bool {
+        matches!(
+            (self, module_type),
+            (ModuleKind::Lecture, ModuleType::Lecture { .. })
+                | (ModuleKind::Lab, ModuleType::Lab)
+                | (ModuleKind::Seminar, ModuleType::Seminar { .. })
+                | (ModuleKind::Thesis, ModuleType::Thesis)
+                | (ModuleKind::KeyQualification, ModuleType::KeyQualification)
+        )
+    }
+}
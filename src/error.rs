@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+
+use crate::explanation::{Explanation, Reason};
+use crate::model::{SemesterDegree, Speciality, SupplementarySubject};
+
+/// Why the catalog, a plan file or the regulation could not be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file could not be read
+    Read { path: PathBuf, error: io::Error },
+    /// The file is not valid TOML or does not have the expected shape
+    Parse { path: PathBuf, error: toml::de::Error },
+    /// The plan file lists a module that is not in the catalog
+    UnknownModule { identifier: String, semester: i32 },
+    /// The plan file has a preference, target grade or additional entry for a module that is not
+    /// in the catalog
+    UnknownOverride { identifier: String, setting: &'static str },
+    /// The plan file gives a status to a module the semester does not list
+    UnlistedStatus { identifier: String, semester: i32 },
+    /// The plan file names a speciality the catalog does not declare
+    UnknownSpeciality { speciality: Speciality },
+    /// The plan file pins a supplementary subject the catalog does not declare
+    UnknownSubject { subject: SupplementarySubject },
+    /// The regulation has an ECTS exception for a speciality the catalog does not declare
+    UnknownException { speciality: Speciality },
+    /// The regulation asks for no speciality at all
+    NoSpecialities,
+    /// A block of a degree, like the supplementary subject, needs more ECTS than it may have
+    EmptyRange { degree: SemesterDegree, block: &'static str, min_ects: i32, max_ects: i32 },
+    /// The ECTS tolerance is negative, so no degree could reach its ECTS
+    NegativeTolerance { ects: i32 },
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Read { path, error } => {
+                write!(f, "Could not read {}: {}", path.display(), error)
+            }
+            LoadError::Parse { path, error } => {
+                write!(f, "Could not parse {}: {}", path.display(), error)
+            }
+            LoadError::UnknownModule { identifier, semester } => write!(
+                f,
+                "Semester {} lists {:?}, which is not in the catalog",
+                semester, identifier
+            ),
+            LoadError::UnknownOverride { identifier, setting } => write!(
+                f,
+                "The plan file lists {:?} in {}, but it is not in the catalog",
+                identifier, setting
+            ),
+            LoadError::UnlistedStatus { identifier, semester } => write!(
+                f,
+                "{:?} has a status but is not listed in semester {}",
+                identifier, semester
+            ),
+            LoadError::UnknownSpeciality { speciality } => write!(
+                f,
+                "The plan file names the speciality {}, which the catalog does not declare",
+                speciality
+            ),
+            LoadError::UnknownSubject { subject } => write!(
+                f,
+                "The plan file pins the subject {}, which the catalog does not declare",
+                subject
+            ),
+            LoadError::UnknownException { speciality } => write!(
+                f,
+                "The regulation has an ECTS exception for {}, which the catalog does not declare",
+                speciality
+            ),
+            LoadError::NoSpecialities => {
+                write!(f, "The regulation has to ask for at least one speciality")
+            }
+            LoadError::EmptyRange { degree, block, min_ects, max_ects } => write!(
+                f,
+                "The {} of the {:?} needs at least {} ECTS but may have at most {}",
+                block, degree, min_ects, max_ects
+            ),
+            LoadError::NegativeTolerance { ects } => {
+                write!(f, "The ECTS tolerance can't be negative, but is {}", ects)
+            }
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Read { error, .. } => Some(error),
+            LoadError::Parse { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn read_toml<T: DeserializeOwned>(path: &Path) -> Result<T, LoadError> {
+    let content = fs::read_to_string(path).map_err(|error| LoadError::Read {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    toml::from_str(&content).map_err(|error| LoadError::Parse {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PlanError {
+    /// A module requires an identifier that is not part of the plan
+    UnknownRequirement { module: String, requirement: String },
+    /// A root module does not have the ECTS the regulation prescribes
+    RootModuleEcts { modules: Vec<String>, ects: i32 },
+    /// The speciality choice pins more specialities than the regulation asks for
+    TooManyPinnedSpecialities { count: usize, max: usize },
+    /// The speciality choice names a speciality the catalog does not declare
+    UnknownSpeciality { speciality: Speciality },
+    /// The catalog declares no speciality to pick from
+    NoSpecialities,
+    /// The z3 sorts built from the model types do not have the expected shape
+    Encoding { message: String },
+    /// No plan satisfies all rules, the reasons form a (minimal) unsat core
+    Unsatisfiable { reasons: Vec<Reason> },
+    /// The solver gave up, usually because of the timeout
+    SolverUnknown { reason: Option<String> },
+    /// The model returned by the solver does not fit the encoding
+    Decoding { message: String },
+}
+
+impl Display for PlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::UnknownRequirement { module, requirement } => {
+                write!(f, "{} requires the unknown module {:?}", module, requirement)
+            }
+            PlanError::RootModuleEcts { modules, ects } => write!(
+                f,
+                "Root modules have to have {} ECTS: {}",
+                ects,
+                modules.join(", ")
+            ),
+            PlanError::TooManyPinnedSpecialities { count, max } => write!(
+                f,
+                "At most {} specialities can be pinned, not {}",
+                max, count
+            ),
+            PlanError::UnknownSpeciality { speciality } => {
+                write!(f, "{} is not a speciality of the catalog", speciality)
+            }
+            PlanError::NoSpecialities => write!(f, "The catalog declares no speciality"),
+            PlanError::Unsatisfiable { reasons } => write!(f, "{}", Explanation(reasons)),
+            PlanError::SolverUnknown { reason } => write!(
+                f,
+                "The solver could not decide: {}",
+                reason.as_deref().unwrap_or("no reason given")
+            ),
+            PlanError::Encoding { message } => write!(f, "Could not encode the plan: {}", message),
+            PlanError::Decoding { message } => {
+                write!(f, "Could not decode the solver model: {}", message)
+            }
+        }
+    }
+}
+
+impl Error for PlanError {}
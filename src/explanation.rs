@@ -0,0 +1,371 @@
+use std::fmt::{Display, Formatter};
+
+use crate::model::{
+    CorequisiteTiming, SemesterDegree, SemesterType, Speciality, SupplementarySubject,
+};
+
+/// Why a constraint was added to the solver. Every tracked assertion carries one, so an unsat core
+/// can be reported in terms of modules, semesters and rules instead of raw tracking literals.
+///
+/// ECTS values are stored as half ECTS, like everywhere else in the model.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Reason {
+    DistinctSpecialties,
+    PinnedSpeciality { speciality: Speciality },
+    SpecialityCandidates { specialty: usize },
+    ExcludedSpeciality { speciality: Speciality },
+    ModuleSpecialities { module: String },
+    BachelorModule { module: String },
+    AdditionalModule { module: String },
+    Forced { module: String },
+    Excluded { module: String },
+    WithinPlan { module: String, semester_count: usize },
+    Scheduled { module: String, semester: i32 },
+    Retake { module: String, semester: i32 },
+    NoAttemptsLeft { module: String },
+    SemesterMaxEcts { semester: i32, half_ects: i32 },
+    SemesterMinEcts { semester: i32, half_ects: i32 },
+    BachelorMinEcts { half_ects: i32 },
+    BachelorMaxEcts { half_ects: i32 },
+    BachelorRootModules { count: i32 },
+    MasterRootModules { count: i32 },
+    MasterLabEcts { half_ects: i32 },
+    MasterSeminarEcts { half_ects: i32 },
+    MasterLabSeminarEcts { half_ects: i32 },
+    MasterMaxEcts { half_ects: i32 },
+    MasterMinEcts { half_ects: i32 },
+    Requirement { module: String, required: String },
+    Corequisite { module: String, corequisite: String, timing: CorequisiteTiming },
+    MutuallyExclusive { group: String, modules: Vec<String> },
+    Proseminar,
+    Thesis { degree: SemesterDegree },
+    ThesisStart { module: String, half_ects: i32 },
+    SemesterType { module: String, semester_type: SemesterType },
+    SemesterDegree { module: String, semester: i32, degree: SemesterDegree },
+    Enrollment { semester: i32, degree: SemesterDegree },
+    BachelorBeforeMaster { module: String },
+    EarlyMasterEcts { half_ects: i32 },
+    SpecialityEcts { specialty: usize, half_ects: i32 },
+    SpecialityEctsWithoutRoot { specialty: usize },
+    PinnedSupplementarySubject { degree: SemesterDegree, subject: SupplementarySubject },
+    SupplementarySubject { module: String, degree: SemesterDegree },
+    SupplementaryMinEcts { degree: SemesterDegree, half_ects: i32 },
+    SupplementaryMaxEcts { degree: SemesterDegree, half_ects: i32 },
+    KeyQualificationMinEcts { degree: SemesterDegree, half_ects: i32 },
+    KeyQualificationMaxEcts { degree: SemesterDegree, half_ects: i32 },
+}
+
+fn ects(half_ects: i32) -> f64 {
+    f64::from(half_ects) / 2.0
+}
+
+fn ordinal(specialty: usize) -> String {
+    match specialty {
+        0 => "first".to_string(),
+        1 => "second".to_string(),
+        2 => "third".to_string(),
+        _ => format!("{}th", specialty + 1),
+    }
+}
+
+impl Reason {
+    /// Alternative changes to the inputs that would each drop this constraint or make it weaker
+    pub fn relaxations(&self) -> Vec<String> {
+        let relaxation = match self {
+            Reason::Forced { module } => format!("lower the preference for {}", module),
+            Reason::Excluded { module } => format!("raise the preference for {}", module),
+            Reason::AdditionalModule { module } => {
+                format!("stop declaring {} as additional", module)
+            }
+            Reason::PinnedSpeciality { speciality } => format!("unpin {}", speciality),
+            Reason::SpecialityCandidates { .. } => "add more candidate specialities".to_string(),
+            Reason::ExcludedSpeciality { speciality } => format!("stop excluding {}", speciality),
+            Reason::Scheduled { module, semester } => {
+                format!("remove {} from semester {}", module, semester)
+            }
+            Reason::Retake { module, .. } => format!("add another semester to retake {}", module),
+            Reason::WithinPlan { module, .. } | Reason::SemesterType { module, .. } => {
+                return vec!["add another semester".to_string(), format!("drop {}", module)];
+            }
+            Reason::SemesterMaxEcts { semester, half_ects } => format!(
+                "raise the maximum of semester {} above {} ECTS",
+                semester,
+                ects(*half_ects)
+            ),
+            Reason::SemesterMinEcts { semester, half_ects } => format!(
+                "lower the minimum of semester {} below {} ECTS",
+                semester,
+                ects(*half_ects)
+            ),
+            Reason::BachelorMaxEcts { .. } | Reason::MasterMaxEcts { .. } => {
+                "raise ects_tolerance in the regulation".to_string()
+            }
+            Reason::Requirement { module, required } => {
+                return vec![
+                    format!("drop {}", module),
+                    format!("schedule {} earlier", required),
+                ];
+            }
+            Reason::Corequisite { module, corequisite, .. } => {
+                return vec![
+                    format!("drop {}", module),
+                    format!("move {} next to {}", corequisite, module),
+                ];
+            }
+            Reason::MutuallyExclusive { modules, .. } => {
+                format!("lower the preference for all but one of {}", modules.join(", "))
+            }
+            Reason::ThesisStart { module, .. } => format!("schedule {} later", module),
+            Reason::SemesterDegree { module, semester, .. } => {
+                return vec![
+                    format!("allow both degrees in semester {}", semester),
+                    format!("move {}", module),
+                ];
+            }
+            Reason::Enrollment { semester, .. } => {
+                format!("allow both degrees in semester {}", semester)
+            }
+            Reason::PinnedSupplementarySubject { subject, .. } => format!("unpin {}", subject),
+            Reason::SupplementaryMaxEcts { .. } => {
+                "count some modules of the supplementary subject as additional".to_string()
+            }
+            Reason::KeyQualificationMaxEcts { .. } => {
+                "count some key qualifications as additional".to_string()
+            }
+            Reason::EarlyMasterEcts { .. } => {
+                "raise max_early_ects in the regulation".to_string()
+            }
+            _ => return vec![],
+        };
+        vec![relaxation]
+    }
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::DistinctSpecialties => write!(f, "The specialties have to differ"),
+            Reason::PinnedSpeciality { speciality } => write!(f, "{} is pinned", speciality),
+            Reason::SpecialityCandidates { specialty } => write!(
+                f,
+                "The {} specialty has to be one of the candidates",
+                ordinal(*specialty)
+            ),
+            Reason::ExcludedSpeciality { speciality } => {
+                write!(f, "{} is excluded", speciality)
+            }
+            Reason::ModuleSpecialities { module } => {
+                write!(f, "If {} is used it has to count toward one of its specialties", module)
+            }
+            Reason::BachelorModule { module } => {
+                write!(f, "{} can only count toward the bachelor or as additional", module)
+            }
+            Reason::AdditionalModule { module } => {
+                write!(f, "{} is declared an additional achievement", module)
+            }
+            Reason::Forced { module } => write!(f, "{} is a must", module),
+            Reason::Excluded { module } => write!(f, "{} is excluded", module),
+            Reason::WithinPlan { module, semester_count } => write!(
+                f,
+                "{} has to be placed in one of the {} planned semesters",
+                module, semester_count
+            ),
+            Reason::Scheduled { module, semester } => {
+                write!(f, "{} is already taken in semester {}", module, semester)
+            }
+            Reason::Retake { module, semester } => write!(
+                f,
+                "{} was failed in semester {} and has to be retaken later",
+                module, semester
+            ),
+            Reason::NoAttemptsLeft { module } => {
+                write!(f, "{} was failed and has no attempts left", module)
+            }
+            Reason::SemesterMaxEcts { semester, half_ects } => write!(
+                f,
+                "Semester {} may have at most {} ECTS",
+                semester,
+                ects(*half_ects)
+            ),
+            Reason::SemesterMinEcts { semester, half_ects } => write!(
+                f,
+                "Semester {} needs at least {} ECTS",
+                semester,
+                ects(*half_ects)
+            ),
+            Reason::BachelorMinEcts { half_ects } => {
+                write!(f, "The bachelor needs at least {} ECTS", ects(*half_ects))
+            }
+            Reason::BachelorMaxEcts { half_ects } => {
+                write!(f, "The bachelor may have at most {} ECTS", ects(*half_ects))
+            }
+            Reason::BachelorRootModules { count } => {
+                write!(f, "The bachelor needs at least {} root modules", count)
+            }
+            Reason::MasterRootModules { count } => {
+                write!(f, "The master needs at least {} root modules", count)
+            }
+            Reason::MasterLabEcts { half_ects } => {
+                write!(f, "The master needs at least {} ECTS of labs", ects(*half_ects))
+            }
+            Reason::MasterSeminarEcts { half_ects } => write!(
+                f,
+                "The master needs at least {} ECTS of seminars",
+                ects(*half_ects)
+            ),
+            Reason::MasterLabSeminarEcts { half_ects } => write!(
+                f,
+                "The master needs at least {} ECTS of labs and seminars",
+                ects(*half_ects)
+            ),
+            Reason::MasterMaxEcts { half_ects } => {
+                write!(f, "The master may count at most {} ECTS", ects(*half_ects))
+            }
+            Reason::MasterMinEcts { half_ects } => {
+                write!(f, "The master needs at least {} counted ECTS", ects(*half_ects))
+            }
+            Reason::Requirement { module, required } => {
+                write!(f, "{} has to be taken before {}", required, module)
+            }
+            Reason::MutuallyExclusive { group, modules } => write!(
+                f,
+                "Only one of {} can be used ({})",
+                modules.join(", "),
+                group
+            ),
+            Reason::Corequisite { module, corequisite, timing } => match timing {
+                CorequisiteTiming::SameSemester => {
+                    write!(f, "{} has to be taken together with {}", corequisite, module)
+                }
+                CorequisiteTiming::SameOrEarlier => write!(
+                    f,
+                    "{} has to be taken together with or before {}",
+                    corequisite, module
+                ),
+            },
+            Reason::Proseminar => write!(f, "The bachelor needs at least one proseminar"),
+            Reason::Thesis { degree } => write!(f, "The {:?} needs exactly one thesis", degree),
+            Reason::ThesisStart { module, half_ects } => write!(
+                f,
+                "{} can only be started after {} ECTS are passed",
+                module,
+                ects(*half_ects)
+            ),
+            Reason::SemesterType { module, semester_type } => {
+                write!(f, "{} is only offered in the {:?} semester", module, semester_type)
+            }
+            Reason::SemesterDegree { module, semester, degree } => write!(
+                f,
+                "If {} is in semester {} it has to count toward the {:?}",
+                module, semester, degree
+            ),
+            Reason::Enrollment { semester, degree } => {
+                write!(f, "Semester {} can only be enrolled in the {:?}", semester, degree)
+            }
+            Reason::BachelorBeforeMaster { module } => {
+                write!(f, "{} has to be finished before enrolling in the master", module)
+            }
+            Reason::EarlyMasterEcts { half_ects } => write!(
+                f,
+                "At most {} master ECTS may be taken during the bachelor",
+                ects(*half_ects)
+            ),
+            Reason::SpecialityEcts { specialty, half_ects } => write!(
+                f,
+                "The {} specialty needs at least {} ECTS",
+                ordinal(*specialty),
+                ects(*half_ects)
+            ),
+            Reason::SpecialityEctsWithoutRoot { specialty } => write!(
+                f,
+                "The {} specialty needs enough ECTS outside of root modules",
+                ordinal(*specialty)
+            ),
+            Reason::PinnedSupplementarySubject { degree, subject } => write!(
+                f,
+                "{} is pinned as supplementary subject of the {:?}",
+                subject, degree
+            ),
+            Reason::SupplementarySubject { module, degree } => write!(
+                f,
+                "{} only counts toward the {:?} if its subject is the supplementary subject",
+                module, degree
+            ),
+            Reason::SupplementaryMinEcts { degree, half_ects } => write!(
+                f,
+                "The supplementary subject of the {:?} needs at least {} ECTS",
+                degree,
+                ects(*half_ects)
+            ),
+            Reason::SupplementaryMaxEcts { degree, half_ects } => write!(
+                f,
+                "The supplementary subject of the {:?} may have at most {} ECTS",
+                degree,
+                ects(*half_ects)
+            ),
+            Reason::KeyQualificationMinEcts { degree, half_ects } => write!(
+                f,
+                "The {:?} needs at least {} ECTS of key qualifications",
+                degree,
+                ects(*half_ects)
+            ),
+            Reason::KeyQualificationMaxEcts { degree, half_ects } => write!(
+                f,
+                "The {:?} may have at most {} ECTS of key qualifications",
+                degree,
+                ects(*half_ects)
+            ),
+        }
+    }
+}
+
+/// A readable account of why no plan exists, built from a (minimal) unsat core
+pub struct Explanation<'a>(pub &'a [Reason]);
+
+impl Display for Explanation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No plan satisfies all rules, these constraints conflict:")?;
+        for reason in self.0 {
+            write!(f, "\n\t{}", reason)?;
+        }
+
+        // Several reasons often share a relaxation, like adding another semester
+        let mut relaxations: Vec<String> = vec![];
+        for relaxation in self.0.iter().flat_map(Reason::relaxations) {
+            if !relaxations.contains(&relaxation) {
+                relaxations.push(relaxation);
+            }
+        }
+        if !relaxations.is_empty() {
+            write!(f, "\nTry to {}", relaxations.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_each_relaxation_once() {
+        let reasons = [
+            Reason::WithinPlan {
+                module: "y".to_string(),
+                semester_count: 6,
+            },
+            Reason::WithinPlan {
+                module: "x".to_string(),
+                semester_count: 6,
+            },
+            Reason::Requirement {
+                module: "x".to_string(),
+                required: "y".to_string(),
+            },
+        ];
+        let explanation = Explanation(&reasons).to_string();
+        assert!(explanation.ends_with(
+            "\nTry to add another semester or drop y or drop x or schedule y earlier"
+        ));
+    }
+}
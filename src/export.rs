@@ -0,0 +1,142 @@
+use serde::Serialize;
+
+use crate::model::{
+    CountsToward, Degree, Grade, Module, ModuleStatus, ModuleType, Plan, Semester, SemesterDegree,
+    SemesterType, Speciality, SupplementarySubject,
+};
+
+/// Machine-readable form of a solved plan. ECTS are exported as whole ECTS instead of half ECTS.
+#[derive(Debug, Serialize)]
+pub struct PlanExport {
+    pub specialities: Vec<Speciality>,
+    pub semesters: Vec<SemesterExport>,
+    pub bachelor_ects: f64,
+    pub master_ects: f64,
+    /// ECTS of additional achievements, counted toward neither degree
+    pub additional_ects: f64,
+    pub bachelor_supplementary_subject: Option<SupplementarySubject>,
+    pub master_supplementary_subject: Option<SupplementarySubject>,
+    /// Number of the first semester enrolled in the master
+    pub master_start: Option<i32>,
+    /// Identifiers of master modules taken during the bachelor
+    pub early_master_modules: Vec<String>,
+    /// Projected from passed and target grades
+    pub bachelor_grade: Option<Grade>,
+    pub master_grade: Option<Grade>,
+    pub violated_recommendations: Vec<ViolatedRecommendation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ViolatedRecommendation {
+    pub module: String,
+    pub recommendation: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SemesterExport {
+    pub number: i32,
+    pub semester_type: SemesterType,
+    pub degrees: Vec<SemesterDegree>,
+    pub ects: f64,
+    pub modules: Vec<ModuleExport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleExport {
+    pub identifier: String,
+    pub name: String,
+    pub module_type: ModuleType,
+    pub ects: f64,
+    /// Number of semesters the module is spread over
+    pub duration: usize,
+    /// Which of these semesters this one is, starting at 1
+    pub part: usize,
+    /// ECTS counted in this semester
+    pub semester_ects: f64,
+    pub counts_toward: CountsToward,
+    pub speciality: Option<Speciality>,
+    pub subject: Option<SupplementarySubject>,
+    pub status: ModuleStatus,
+    pub grade: Option<Grade>,
+}
+
+fn ects(half_ects: i32) -> f64 {
+    f64::from(half_ects) / 2.0
+}
+
+impl ModuleExport {
+    /// The module as listed in the semester `offset` semesters after it was started
+    fn new(module: &Module, offset: usize) -> Self {
+        let (counts_toward, speciality) = match &module.degree {
+            Degree::Bachelor => (CountsToward::Bachelor, None),
+            Degree::Master(specialities) => (CountsToward::Master, specialities.first().cloned()),
+            Degree::Additional => (CountsToward::Additional, None),
+        };
+        ModuleExport {
+            identifier: module.identifier.clone(),
+            name: module.name.clone(),
+            module_type: module.module_type.clone(),
+            ects: ects(module.half_ects),
+            duration: module.duration(),
+            part: offset + 1,
+            semester_ects: ects(module.half_ects_per_semester()[offset]),
+            counts_toward,
+            speciality,
+            subject: module.subject.clone(),
+            status: module.status,
+            grade: module.grade(),
+        }
+    }
+}
+
+impl From<&Semester> for SemesterExport {
+    fn from(semester: &Semester) -> Self {
+        SemesterExport {
+            number: semester.number,
+            semester_type: semester.semester_type.clone(),
+            degrees: semester.degrees.clone(),
+            ects: ects(semester.half_ects()),
+            modules: semester
+                .modules
+                .iter()
+                .map(|module| ModuleExport::new(module, 0))
+                .chain(
+                    semester
+                        .continued
+                        .iter()
+                        .map(|(module, offset)| ModuleExport::new(module, *offset)),
+                )
+                .collect(),
+        }
+    }
+}
+
+impl From<&Plan> for PlanExport {
+    fn from(plan: &Plan) -> Self {
+        PlanExport {
+            specialities: plan.specialties().iter().flatten().cloned().collect(),
+            semesters: plan.semesters().iter().map(SemesterExport::from).collect(),
+            bachelor_ects: ects(plan.bachelor_half_ects()),
+            master_ects: ects(plan.master_half_ects()),
+            additional_ects: ects(plan.additional_half_ects()),
+            bachelor_supplementary_subject: plan.supplementary_subject(SemesterDegree::Bachelor).cloned(),
+            master_supplementary_subject: plan.supplementary_subject(SemesterDegree::Master).cloned(),
+            master_start: plan.master_start(),
+            early_master_modules: plan
+                .early_master_modules()
+                .into_iter()
+                .map(|module| module.identifier.clone())
+                .collect(),
+            bachelor_grade: plan.average_grade(SemesterDegree::Bachelor),
+            master_grade: plan.average_grade(SemesterDegree::Master),
+            violated_recommendations: plan
+                .violated_recommendations()
+                .into_iter()
+                .map(|(module, recommendation)| ViolatedRecommendation {
+                    module: module.identifier.clone(),
+                    recommendation: recommendation.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
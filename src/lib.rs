@@ -0,0 +1,41 @@
+//! Plan a bachelor and master in computer science with an SMT solver.
+//!
+//! A [`Catalog`](catalog::Catalog) lists the modules that can be taken, a
+//! [`PlanFile`](plan_file::PlanFile) describes the semester timeline with the modules already
+//! taken and a [`Regulation`](regulation::Regulation) holds the rules of the examination
+//! regulation. From those a [`Plan`](model::Plan) is built and solved, and the solutions can be
+//! rendered with [`SolutionGroups`](render::SolutionGroups).
+//!
+//! ```no_run
+//! use studienplan::catalog::Catalog;
+//! use studienplan::model::{Plan, SolveOptions};
+//! use studienplan::plan_file::PlanFile;
+//! use studienplan::regulation::Regulation;
+//! use studienplan::render::SolutionGroups;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let catalog = Catalog::from_file("catalog.toml")?;
+//! let plan_file = PlanFile::from_file("plan.toml")?;
+//! let regulation = Regulation::from_file("regulation.toml")?;
+//!
+//! let semesters = plan_file.semesters(&catalog)?;
+//! let modules = plan_file.remaining_modules(&catalog);
+//! let solutions = Plan::from_semesters_with_modules(&semesters, &modules, &catalog, &regulation)?
+//!     .with_speciality_choice(plan_file.specialities.clone())?
+//!     .with_supplementary_choice(plan_file.supplementary_subjects.clone())
+//!     .get_solutions(&SolveOptions::default().with_limit(10))?;
+//! println!("{}", SolutionGroups::new(&solutions).render_text());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod catalog;
+pub mod error;
+pub mod explanation;
+pub mod export;
+pub mod model;
+pub mod plan_file;
+pub mod regulation;
+pub mod render;
+pub mod validation;
+mod z3model;
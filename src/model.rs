@@ -1,97 +1,239 @@
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::Infallible;
 use std::f64;
-use std::fmt::{write, Display, Formatter};
-use std::hash::Hash;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::ops::Range;
 use std::str::FromStr;
+use std::sync::Once;
 
 use crate::model::Degree::Bachelor;
 use crate::model::SemesterType::Unknown;
-use crate::model::Speciality::Telematics;
-use crate::z3model::Z3Module;
+use crate::catalog::Catalog;
+use crate::error::PlanError;
+use crate::explanation::Reason;
+use crate::regulation::Regulation;
+use crate::z3model::{DegreeSort, TrackedAssertions, TrackingSolver, Z3Module};
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+use strum::{Display, EnumString};
 use z3::ast::{Ast, Bool, Datatype, Int};
-use z3::{Config, Context, FuncDecl, SatResult, Solver, Sort, Symbol};
+use z3::{set_global_param, Config, Context, FuncDecl, Model, Optimize, SatResult, Sort, Symbol};
 
-#[derive(
-    Debug,
-    Display,
-    Eq,
-    PartialEq,
-    Clone,
-    Copy,
-    Serialize,
-    Deserialize,
-    Ord,
-    PartialOrd,
-    EnumIter,
-    EnumString,
-    Hash,
-)]
-pub enum Speciality {
-    Theoretics,
-    Algorithms,
-    Security,
-    Parallelism,
-    SoftwareEngineering,
-    Embedded,
-    Telematics,
-    InformationSystems,
-    ComputerGraphics,
-    Robotics,
-    AiOverlords,
-    SystemArchitecture,
-}
+/// A speciality (Vertiefungsfach), the catalog declares which ones a programme has
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, Ord, PartialOrd, Hash)]
+#[serde(transparent)]
+pub struct Speciality(String);
 
 impl Speciality {
-    pub fn z3_enum(ctx: &Context) -> (Sort, Vec<FuncDecl>, Vec<FuncDecl>) {
-        let names: Vec<_> = Self::iter()
-            .map(|entry| entry.to_string())
-            .map(Symbol::from)
+    pub fn new(name: impl Into<String>) -> Speciality {
+        Speciality(name.into())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    pub fn z3_enum<'ctx>(
+        ctx: &'ctx Context,
+        specialities: &[Speciality],
+    ) -> (Sort<'ctx>, Vec<FuncDecl<'ctx>>, Vec<FuncDecl<'ctx>>) {
+        let names: Vec<_> = specialities
+            .iter()
+            .map(|entry| Symbol::from(entry.name()))
             .collect();
         let names = &names[..];
         Sort::enumeration(ctx, Symbol::from("Specialty"), names)
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Ord, PartialOrd, Hash)]
+impl Display for Speciality {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Speciality {
+    type Err = Infallible;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(Speciality::new(name))
+    }
+}
+
+/// Subject outside of computer science that both degrees require (Ergänzungsfach), the catalog
+/// declares which ones can be picked
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, Ord, PartialOrd, Hash)]
+#[serde(transparent)]
+pub struct SupplementarySubject(String);
+
+impl SupplementarySubject {
+    pub fn new(name: impl Into<String>) -> SupplementarySubject {
+        SupplementarySubject(name.into())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    pub fn z3_enum<'ctx>(
+        ctx: &'ctx Context,
+        subjects: &[SupplementarySubject],
+    ) -> (Sort<'ctx>, Vec<FuncDecl<'ctx>>, Vec<FuncDecl<'ctx>>) {
+        let names: Vec<_> = subjects
+            .iter()
+            .map(|entry| Symbol::from(entry.name()))
+            .collect();
+        let names = &names[..];
+        Sort::enumeration(ctx, Symbol::from("SupplementarySubject"), names)
+    }
+}
+
+impl Display for SupplementarySubject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What a module can count toward according to the catalog
+#[derive(Debug, Eq, PartialEq, Clone, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Degree {
     Bachelor,
+    /// A master module, counting toward one of the given specialities if any are listed
     Master(Vec<Speciality>),
+    /// An additional achievement (Zusatzleistung) that counts toward neither degree
+    Additional,
 }
 
+impl Display for Degree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Degree::Bachelor => write!(f, "Bachelor"),
+            Degree::Master(specialities) => {
+                let names: Vec<_> = specialities.iter().map(Speciality::name).collect();
+                write!(f, "Master([{}])", names.join(", "))
+            }
+            Degree::Additional => write!(f, "Additional"),
+        }
+    }
+}
+
+/// Degree a student can be enrolled in during a semester
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize, Hash)]
 pub enum SemesterDegree {
     Bachelor,
     Master,
 }
 
-impl SemesterDegree {
-    pub fn z3_enum(ctx: &Context) -> (Sort, Vec<FuncDecl>, Vec<FuncDecl>) {
-        let names = ["Bachelor", "Master"].map(Symbol::from);
+/// What the ECTS of a used module are counted toward
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize, Hash)]
+pub enum CountsToward {
+    Bachelor,
+    Master,
+    Additional,
+}
+
+impl CountsToward {
+    pub fn z3_enum(ctx: &Context) -> (Sort<'_>, Vec<FuncDecl<'_>>, Vec<FuncDecl<'_>>) {
+        let names = ["Bachelor", "Master", "Additional"].map(Symbol::from);
         let names = &names[..];
         Sort::enumeration(ctx, Symbol::from("Degree"), names)
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[derive(
+    Clone,
+    Debug,
+    Display,
+    Eq,
+    PartialEq,
+    Hash,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    EnumString,
+)]
 pub enum SemesterType {
     Summer,
     Winter,
+    /// Offered in a semester not known yet, fits either
     Unknown,
 }
 
+/// A semester of the timeline, with the degrees the student can be enrolled in and the modules
+/// taken in it
 #[derive(Clone, Debug, Hash)]
 pub struct Semester {
     pub(crate) number: i32,
     pub(crate) degrees: Vec<SemesterDegree>,
     pub(crate) ects: Range<i32>,
     pub(crate) modules: Vec<Module>,
+    /// Modules started in an earlier semester that are still taken in this one, with the number
+    /// of semesters since their start
+    pub(crate) continued: Vec<(Module, usize)>,
     pub(crate) semester_type: SemesterType,
 }
 
+impl Semester {
+    /// An empty semester allowing between `half_ects.start` and `half_ects.end` half ECTS
+    pub fn new(
+        number: i32,
+        semester_type: SemesterType,
+        degrees: Vec<SemesterDegree>,
+        half_ects: Range<i32>,
+    ) -> Semester {
+        Semester {
+            number,
+            degrees,
+            ects: half_ects,
+            modules: vec![],
+            continued: vec![],
+            semester_type,
+        }
+    }
+
+    /// Modules that were already taken in this semester, failed attempts are retaken later
+    pub fn with_modules(self, modules: Vec<Module>) -> Semester {
+        Semester { modules, ..self }
+    }
+
+    pub fn number(&self) -> i32 {
+        self.number
+    }
+
+    pub fn semester_type(&self) -> &SemesterType {
+        &self.semester_type
+    }
+
+    pub fn degrees(&self) -> &[SemesterDegree] {
+        &self.degrees
+    }
+
+    /// Modules started in this semester
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    /// Modules started in an earlier semester that are still taken in this one, with the number
+    /// of semesters since their start
+    pub fn continued_modules(&self) -> &[(Module, usize)] {
+        &self.continued
+    }
+
+    /// Half ECTS counted in this semester. Modules spanning several semesters only count their
+    /// share of it, failed attempts not at all.
+    pub fn half_ects(&self) -> i32 {
+        let started = self.modules.iter().map(|module| (module, 0));
+        let continued = self.continued.iter().map(|(module, offset)| (module, *offset));
+        started
+            .chain(continued)
+            .filter(|(module, _)| !module.status.is_failed())
+            .filter_map(|(module, offset)| module.half_ects_per_semester().get(offset).copied())
+            .sum()
+    }
+}
+
 impl Display for Semester {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut modules = self.modules.clone();
@@ -99,10 +241,12 @@ impl Display for Semester {
         let modules = modules
             .iter()
             .map(|module| format!("{}", module))
+            .chain(self.continued.iter().map(|(module, offset)| {
+                format!("{} (part {} of {})", module, offset + 1, module.duration())
+            }))
             .collect::<Vec<_>>()
             .join("\n\t");
-        let total_ects: i32 = self.modules.iter().map(|module| module.half_ects).sum();
-        let total_ects = f64::from(total_ects) / 2.0;
+        let total_ects = f64::from(self.half_ects()) / 2.0;
         write!(
             f,
             "Semester {} ({:?}) - total {} ECTS:\n\t{}",
@@ -111,232 +255,1159 @@ impl Display for Semester {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum ModuleType {
+    /// A lecture, root modules (Stammmodule) have the ECTS set by the regulation
     Lecture { is_root: bool },
+    /// A lab (Praktikum)
     Lab,
+    /// A seminar, the bachelor requires a proseminar
     Seminar { is_pro: bool },
+    Thesis,
+    /// Interdisciplinary qualifications (Überfachliche Qualifikationen) like HOC or ZAK courses
+    KeyQualification,
 }
 
 impl ModuleType {
+    /// German prefix put in front of the module name when printing it
     pub fn prefix(&self) -> &'static str {
         match &self {
             ModuleType::Lecture { is_root: _ } => "",
             ModuleType::Lab => "Praktikum ",
             ModuleType::Seminar { is_pro: true } => "Proseminar ",
             ModuleType::Seminar { is_pro: false } => "Seminar ",
+            ModuleType::Thesis => "",
+            ModuleType::KeyQualification => "Schlüsselqualifikation ",
+        }
+    }
+}
+
+/// How much a student wants a module. `Must` and `Never` are hard constraints, `Want` and `Avoid`
+/// only count toward the preference score.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preference {
+    Must,
+    Want,
+    #[default]
+    Neutral,
+    Avoid,
+    Never,
+}
+
+impl Preference {
+    /// Contribution of a used module to the preference score
+    pub fn score(&self) -> i64 {
+        match self {
+            Preference::Want => 1,
+            Preference::Avoid => -1,
+            Preference::Must | Preference::Neutral | Preference::Never => 0,
+        }
+    }
+}
+
+/// A grade between 1.0 and 4.0, stored in tenths
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "f64", into = "f64")]
+pub struct Grade(u8);
+
+impl TryFrom<f64> for Grade {
+    type Error = String;
+
+    fn try_from(grade: f64) -> Result<Self, Self::Error> {
+        if (1.0..=4.0).contains(&grade) {
+            Ok(Grade((grade * 10.0).round() as u8))
+        } else {
+            Err(format!("{} is not a passing grade between 1.0 and 4.0", grade))
+        }
+    }
+}
+
+impl Grade {
+    /// ECTS-weighted average of the grades, cut off after the first decimal like KIT does
+    pub fn weighted_average(grades: impl IntoIterator<Item = (Grade, i32)>) -> Option<Grade> {
+        let (weighted_sum, half_ects) = grades
+            .into_iter()
+            .fold((0, 0), |(weighted_sum, total), (grade, half_ects)| {
+                (weighted_sum + i32::from(grade.0) * half_ects, total + half_ects)
+            });
+        if half_ects == 0 {
+            return None;
         }
+        u8::try_from(weighted_sum / half_ects).ok().map(Grade)
+    }
+}
+
+impl From<Grade> for f64 {
+    fn from(grade: Grade) -> Self {
+        f64::from(grade.0) / 10.0
+    }
+}
+
+impl Display for Grade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1}", f64::from(*self))
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+/// Where a student stands with a module. Everything but `Failed` keeps the module in the semester
+/// it is listed in, a failed module has to be retaken in a later semester.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModuleStatus {
+    #[default]
+    Planned,
+    Passed {
+        #[serde(default)]
+        grade: Option<Grade>,
+    },
+    InProgress,
+    Failed {
+        /// Unlimited if not given. Only whether any are left matters, the plan schedules the
+        /// retake that is passed.
+        #[serde(default)]
+        attempts_left: Option<u32>,
+    },
+}
+
+impl ModuleStatus {
+    /// Failed modules don't count toward any degree
+    pub fn is_failed(&self) -> bool {
+        matches!(self, ModuleStatus::Failed { .. })
+    }
+}
+
+impl Display for ModuleStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleStatus::Planned => write!(f, "planned"),
+            ModuleStatus::Passed { grade: Some(grade) } => write!(f, "passed with {}", grade),
+            ModuleStatus::Passed { grade: None } => write!(f, "passed"),
+            ModuleStatus::InProgress => write!(f, "in progress"),
+            ModuleStatus::Failed {
+                attempts_left: Some(attempts_left),
+            } => write!(f, "failed, {} attempts left", attempts_left),
+            ModuleStatus::Failed { attempts_left: None } => write!(f, "failed"),
+        }
+    }
+}
+
+/// A prerequisite of a module. In the catalog a plain string is a single module, while
+/// `{ any_of = [...] }` and `{ all_of = [...] }` combine requirements.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Requirement {
+    Module(String),
+    AllOf { all_of: Vec<Requirement> },
+    AnyOf { any_of: Vec<Requirement> },
+}
+
+impl Requirement {
+    /// Every module identifier mentioned in the requirement
+    pub fn identifiers(&self) -> Vec<&String> {
+        match self {
+            Requirement::Module(identifier) => vec![identifier],
+            Requirement::AllOf { all_of: requirements }
+            | Requirement::AnyOf { any_of: requirements } => requirements
+                .iter()
+                .flat_map(Requirement::identifiers)
+                .collect(),
+        }
+    }
+
+    /// Evaluates the requirement, `taken_before` tells whether a module was taken early enough
+    pub fn is_met(&self, taken_before: &impl Fn(&str) -> bool) -> bool {
+        match self {
+            Requirement::Module(identifier) => taken_before(identifier),
+            Requirement::AllOf { all_of } => all_of
+                .iter()
+                .all(|requirement| requirement.is_met(taken_before)),
+            Requirement::AnyOf { any_of } => any_of
+                .iter()
+                .any(|requirement| requirement.is_met(taken_before)),
+        }
+    }
+
+    /// Whether an `any_of` or `all_of` without any entries is nested in the requirement
+    pub fn has_empty_group(&self) -> bool {
+        match self {
+            Requirement::Module(_) => false,
+            Requirement::AllOf { all_of: requirements }
+            | Requirement::AnyOf { any_of: requirements } => {
+                requirements.is_empty() || requirements.iter().any(Requirement::has_empty_group)
+            }
+        }
+    }
+
+    /// Module identifiers that have to be taken no matter which alternatives are chosen
+    pub fn mandatory_identifiers(&self) -> Vec<&String> {
+        match self {
+            Requirement::Module(identifier) => vec![identifier],
+            Requirement::AllOf { all_of } => all_of
+                .iter()
+                .flat_map(Requirement::mandatory_identifiers)
+                .collect(),
+            Requirement::AnyOf { any_of } => match &any_of[..] {
+                [requirement] => requirement.mandatory_identifiers(),
+                _ => vec![],
+            },
+        }
+    }
+}
+
+impl Display for Requirement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let join = |requirements: &[Requirement], separator: &str| {
+            requirements
+                .iter()
+                .map(Requirement::to_string)
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match self {
+            Requirement::Module(identifier) => write!(f, "{}", identifier),
+            Requirement::AllOf { all_of } => write!(f, "({})", join(all_of, " and ")),
+            Requirement::AnyOf { any_of } => write!(f, "({})", join(any_of, " or ")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CorequisiteTiming {
+    /// Both modules are taken in the same semester
+    #[default]
+    SameSemester,
+    /// The co-requisite may also be taken in an earlier semester
+    SameOrEarlier,
+}
+
+/// A module that has to be taken together with another one, like a lab alongside its lecture
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct Corequisite {
+    /// Identifier of the module that has to be taken as well
+    pub module: String,
+    #[serde(default)]
+    pub timing: CorequisiteTiming,
+}
+
+/// A module of the catalog, or one taken in a semester of the plan
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct Module {
     pub(crate) module_type: ModuleType,
     pub(crate) half_ects: i32,
     pub(crate) degree: Degree,
-    pub(crate) name: &'static str,
-    pub(crate) identifier: &'static str,
-    pub(crate) requirements: Vec<&'static str>,
+    pub(crate) name: String,
+    pub(crate) identifier: String,
+    /// Have to be met all together
+    pub(crate) requirements: Vec<Requirement>,
+    /// Recommended prior knowledge, only penalized when not met
+    #[serde(default)]
+    pub(crate) recommendations: Vec<Requirement>,
+    /// Modules that have to be taken alongside this one
+    #[serde(default)]
+    pub(crate) corequisites: Vec<Corequisite>,
+    /// Of all modules sharing a group at most one can be used
+    #[serde(default)]
+    pub(crate) exclusion_groups: Vec<String>,
+    /// How the half ECTS are split over consecutive semesters, empty for a single semester
+    #[serde(default)]
+    pub(crate) half_ects_per_semester: Vec<i32>,
+    /// Semester types the module is offered in
     pub(crate) semesters: Vec<SemesterType>,
-    pub(crate) force: bool,
+    #[serde(default)]
+    pub(crate) preference: Preference,
+    #[serde(default)]
+    pub(crate) status: ModuleStatus,
+    /// Grade the student aims for while the module is not passed yet
+    #[serde(default)]
+    pub(crate) target_grade: Option<Grade>,
+    /// Ungraded modules don't count toward the average grade
+    #[serde(default)]
+    pub(crate) ungraded: bool,
+    /// Supplementary subject the module belongs to instead of a speciality
+    #[serde(default)]
+    pub(crate) subject: Option<SupplementarySubject>,
+}
+
+impl Module {
+    /// A module without requirements that is offered every semester
+    pub fn new(
+        identifier: impl Into<String>,
+        name: impl Into<String>,
+        module_type: ModuleType,
+        half_ects: i32,
+        degree: Degree,
+    ) -> Module {
+        Module {
+            module_type,
+            half_ects,
+            degree,
+            name: name.into(),
+            identifier: identifier.into(),
+            requirements: vec![],
+            recommendations: vec![],
+            corequisites: vec![],
+            exclusion_groups: vec![],
+            half_ects_per_semester: vec![],
+            semesters: vec![SemesterType::Unknown],
+            preference: Preference::default(),
+            status: ModuleStatus::default(),
+            target_grade: None,
+            ungraded: false,
+            subject: None,
+        }
+    }
+
+    /// Requirements that all have to be met in earlier semesters
+    pub fn with_requirements(self, requirements: Vec<Requirement>) -> Module {
+        Module {
+            requirements,
+            ..self
+        }
+    }
+
+    /// Prior knowledge that should be taken in earlier semesters, but does not have to
+    pub fn with_recommendations(self, recommendations: Vec<Requirement>) -> Module {
+        Module {
+            recommendations,
+            ..self
+        }
+    }
+
+    /// Modules that have to be taken in the same or an earlier semester
+    pub fn with_corequisites(self, corequisites: Vec<Corequisite>) -> Module {
+        Module {
+            corequisites,
+            ..self
+        }
+    }
+
+    /// Groups of mutually exclusive modules this module belongs to
+    pub fn with_exclusion_groups(self, exclusion_groups: Vec<String>) -> Module {
+        Module {
+            exclusion_groups,
+            ..self
+        }
+    }
+
+    /// Spreads the module over consecutive semesters, the split has to add up to its half ECTS
+    pub fn with_half_ects_per_semester(self, half_ects_per_semester: Vec<i32>) -> Module {
+        Module {
+            half_ects_per_semester,
+            ..self
+        }
+    }
+
+    /// Semester types the module is offered in
+    pub fn with_semesters(self, semesters: Vec<SemesterType>) -> Module {
+        Module { semesters, ..self }
+    }
+
+    /// Overrides the degree from the catalog, e.g. to declare an additional achievement
+    pub fn with_degree(self, degree: Degree) -> Module {
+        Module { degree, ..self }
+    }
+
+    pub fn with_preference(self, preference: Preference) -> Module {
+        Module { preference, ..self }
+    }
+
+    pub fn with_status(self, status: ModuleStatus) -> Module {
+        Module { status, ..self }
+    }
+
+    pub fn with_target_grade(self, target_grade: Grade) -> Module {
+        Module {
+            target_grade: Some(target_grade),
+            ..self
+        }
+    }
+
+    pub fn with_ungraded(self, ungraded: bool) -> Module {
+        Module { ungraded, ..self }
+    }
+
+    pub fn with_subject(self, subject: SupplementarySubject) -> Module {
+        Module {
+            subject: Some(subject),
+            ..self
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn module_type(&self) -> &ModuleType {
+        &self.module_type
+    }
+
+    pub fn half_ects(&self) -> i32 {
+        self.half_ects
+    }
+
+    pub fn degree(&self) -> &Degree {
+        &self.degree
+    }
+
+    pub fn requirements(&self) -> &[Requirement] {
+        &self.requirements
+    }
+
+    pub fn recommendations(&self) -> &[Requirement] {
+        &self.recommendations
+    }
+
+    pub fn corequisites(&self) -> &[Corequisite] {
+        &self.corequisites
+    }
+
+    pub fn exclusion_groups(&self) -> &[String] {
+        &self.exclusion_groups
+    }
+
+    /// Half ECTS counted in each semester, starting with the one the module is started in
+    pub fn half_ects_per_semester(&self) -> Vec<i32> {
+        if self.half_ects_per_semester.is_empty() {
+            vec![self.half_ects]
+        } else {
+            self.half_ects_per_semester.clone()
+        }
+    }
+
+    /// Number of semesters the module is spread over
+    pub fn duration(&self) -> usize {
+        self.half_ects_per_semester.len().max(1)
+    }
+
+    pub fn semesters(&self) -> &[SemesterType] {
+        &self.semesters
+    }
+
+    pub fn preference(&self) -> Preference {
+        self.preference
+    }
+
+    pub fn status(&self) -> ModuleStatus {
+        self.status
+    }
+
+    pub fn target_grade(&self) -> Option<Grade> {
+        self.target_grade
+    }
+
+    pub fn is_ungraded(&self) -> bool {
+        self.ungraded
+    }
+
+    pub fn subject(&self) -> Option<&SupplementarySubject> {
+        self.subject.as_ref()
+    }
+
+    /// Theses, key qualifications and modules of a supplementary subject count toward the
+    /// master, but toward no speciality
+    pub fn has_speciality(&self) -> bool {
+        !matches!(self.module_type, ModuleType::Thesis | ModuleType::KeyQualification)
+            && self.subject.is_none()
+    }
+
+    /// The grade if the module was passed with one, otherwise the target grade
+    pub fn grade(&self) -> Option<Grade> {
+        match self.status {
+            ModuleStatus::Passed { grade } => grade.or(self.target_grade),
+            _ => self.target_grade,
+        }
+    }
+
+    /// Name and identifier, as used in explanations and diagnostics
+    pub fn label(&self) -> String {
+        format!("{} [{}]", self.name, self.identifier)
+    }
 }
 
 impl Display for Module {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}{} [{}] as {:?} with {} ECTS",
+            "{}{} [{}] as {} with {} ECTS",
             self.module_type.prefix(),
             self.name,
             self.identifier,
             self.degree,
             f64::from(self.half_ects) / 2.0
-        )
+        )?;
+        if let Some(subject) = &self.subject {
+            write!(f, " in {}", subject)?;
+        }
+        if self.duration() > 1 {
+            write!(f, " over {} semesters", self.duration())?;
+        }
+        if self.status != ModuleStatus::Planned {
+            write!(f, " ({})", self.status)?;
+        }
+        if let (ModuleStatus::Planned | ModuleStatus::InProgress, Some(target_grade)) =
+            (self.status, self.target_grade)
+        {
+            write!(f, " aiming for {}", target_grade)?;
+        }
+        Ok(())
+    }
+}
+
+/// What the solver optimizes for when picking among satisfying plans
+#[derive(Clone, Copy, Debug, Display, Eq, PartialEq, Serialize, Deserialize, EnumString)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Objective {
+    /// Finish the last module as early as possible
+    FewestSemesters,
+    /// Take as few ECTS beyond the required ones as possible
+    MinimalOvershoot,
+    /// Keep the ECTS of the semesters still to come close to each other
+    EvenLoad,
+    /// Use as many wanted and as few avoided modules as possible
+    PreferredModules,
+}
+
+/// Supplementary subjects pinned by the student, the solver picks the others
+#[derive(Clone, Debug, Default, Hash, Serialize, Deserialize)]
+pub struct SupplementaryChoice {
+    /// Supplementary subject of the bachelor, picked by the solver if not given
+    pub bachelor: Option<SupplementarySubject>,
+    /// Supplementary subject of the master, picked by the solver if not given
+    pub master: Option<SupplementarySubject>,
+}
+
+/// Restrictions on which specialities the solver may pick, the regulation sets how many
+#[derive(Clone, Debug, Default, Hash, Serialize, Deserialize)]
+pub struct SpecialityChoice {
+    /// Specialities that have to be picked, at most the count of the regulation
+    #[serde(default)]
+    pub pinned: Vec<Speciality>,
+    /// If not empty, the specialities that are not pinned have to be picked from these
+    #[serde(default)]
+    pub candidates: Vec<Speciality>,
+    /// Specialities that must not be picked
+    #[serde(default)]
+    pub excluded: Vec<Speciality>,
+}
+
+/// How the solver searches for plans
+#[derive(Clone, Debug, Default)]
+pub struct SolveOptions {
+    /// Most plans to enumerate, unlimited if not given
+    pub limit: Option<usize>,
+    /// Time after which z3 gives up, no timeout if not given
+    pub timeout_ms: Option<u64>,
+    /// What to optimize for, any satisfying plan is taken if not given
+    pub objective: Option<Objective>,
+}
+
+impl SolveOptions {
+    /// Stops after `limit` plans
+    pub fn with_limit(self, limit: usize) -> SolveOptions {
+        SolveOptions {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Lets z3 give up after `timeout_ms` milliseconds
+    pub fn with_timeout_ms(self, timeout_ms: u64) -> SolveOptions {
+        SolveOptions {
+            timeout_ms: Some(timeout_ms),
+            ..self
+        }
+    }
+
+    /// Optimizes the plan for `objective`
+    pub fn with_objective(self, objective: Objective) -> SolveOptions {
+        SolveOptions {
+            objective: Some(objective),
+            ..self
+        }
+    }
+
+    fn config(&self) -> Config {
+        // Turning 0-1 integers into booleans makes z3 4.8 report satisfiable plans as unsat when
+        // optimizing, and the option is only read globally
+        static DISABLE_ELIM_01: Once = Once::new();
+        DISABLE_ELIM_01.call_once(|| set_global_param("opt.elim_01", "false"));
+        let mut config = Config::new();
+        if let Some(timeout_ms) = self.timeout_ms {
+            config.set_timeout_msec(timeout_ms);
+        }
+        config
     }
 }
 
+/// Whether some plan satisfies all rules, without building one
+#[derive(Clone, Debug)]
+pub enum Verdict {
+    Satisfiable,
+    /// No plan satisfies all rules, the reasons form a (minimal) unsat core
+    Unsatisfiable(Vec<Reason>),
+    /// The solver gave up, usually because of the timeout
+    Unknown,
+}
+
+struct Encoding<'ctx> {
+    z3_modules: Vec<Z3Module<'ctx>>,
+    semester_sums: Vec<Int<'ctx>>,
+    /// Number of recommendations of used modules that are not met
+    violated_recommendations: Int<'ctx>,
+    specialties: Vec<Datatype<'ctx>>,
+    /// Every speciality that can be picked, with its value in the speciality sort
+    speciality_values: Vec<(Speciality, Datatype<'ctx>)>,
+    /// Index of the first semester enrolled in the master
+    master_start: Int<'ctx>,
+    /// Supplementary subjects of the bachelor and the master, unless the catalog declares none
+    supplementary_subjects: Option<[Datatype<'ctx>; 2]>,
+    /// Every supplementary subject that can be picked, with its value in the subject sort
+    subject_values: Vec<(SupplementarySubject, Datatype<'ctx>)>,
+    bachelor: Datatype<'ctx>,
+    additional: Datatype<'ctx>,
+}
+
+/// A study plan of the bachelor and the master. Before solving it holds the semesters and the
+/// modules still to place, a solved plan has every used module in its semester.
 #[derive(Clone, Debug, Hash)]
 pub struct Plan {
     modules: Vec<Module>,
     semesters: Vec<Semester>,
-    specialties: [Option<Speciality>; 2],
+    specialties: Vec<Option<Speciality>>,
+    /// Number of the first semester enrolled in the master, known once solved
+    master_start: Option<i32>,
+    /// Supplementary subjects of the bachelor and the master, known once solved
+    supplementary_subjects: [Option<SupplementarySubject>; 2],
+    /// Supplementary subjects declared by the catalog
+    available_subjects: Vec<SupplementarySubject>,
+    /// Specialities declared by the catalog
+    available_specialities: Vec<Speciality>,
+    speciality_choice: SpecialityChoice,
+    supplementary_choice: SupplementaryChoice,
+    regulation: Regulation,
 }
 
 impl Plan {
+    /// An unsolved plan over `semesters`, the modules already taken in them stay where they are
+    /// and the solver places `remaining_modules`. Failed modules that are not listed again are
+    /// retaken later. Fails if the catalog declares no speciality or a root module does not have
+    /// the ECTS of the regulation.
     pub fn from_semesters_with_modules(
         semesters: &[Semester],
         remaining_modules: &[Module],
-    ) -> Plan {
+        catalog: &Catalog,
+        regulation: &Regulation,
+    ) -> Result<Plan, PlanError> {
+        if catalog.specialities.is_empty() {
+            return Err(PlanError::NoSpecialities);
+        }
         let collected_modules = semesters
             .iter()
-            .flat_map(|semester| semester.modules.iter());
-        let modules: Vec<_> = remaining_modules
+            .flat_map(|semester| semester.modules.iter())
+            .filter(|module| !module.status.is_failed());
+        let mut modules: Vec<_> = remaining_modules
             .iter()
             .chain(collected_modules)
             .cloned()
             .collect();
+        // A failed module that is not listed again still has to be retaken
+        let retakes: Vec<_> = semesters
+            .iter()
+            .flat_map(|semester| &semester.modules)
+            .filter(|module| module.status.is_failed())
+            .collect();
+        for retake in retakes {
+            if !modules.iter().any(|module| module.identifier == retake.identifier) {
+                modules.push(retake.clone().with_status(ModuleStatus::Planned));
+            }
+        }
 
         let plan = Plan {
             modules,
             semesters: semesters.to_vec(),
-            specialties: [None, None],
+            specialties: vec![None; regulation.speciality.count],
+            master_start: None,
+            supplementary_subjects: [None, None],
+            available_subjects: catalog.supplementary_subjects.clone(),
+            available_specialities: catalog.specialities.clone(),
+            speciality_choice: SpecialityChoice::default(),
+            supplementary_choice: SupplementaryChoice::default(),
+            regulation: regulation.clone(),
         };
-        plan.check_basic();
+        plan.check_basic()?;
+
+        Ok(plan)
+    }
+
+    /// Restricts the specialities the solver may pick. Fails if more specialities are pinned than
+    /// the regulation asks for, or if a pinned or candidate speciality is not in the catalog.
+    pub fn with_speciality_choice(
+        self,
+        speciality_choice: SpecialityChoice,
+    ) -> Result<Plan, PlanError> {
+        if speciality_choice.pinned.len() > self.regulation.speciality.count {
+            return Err(PlanError::TooManyPinnedSpecialities {
+                count: speciality_choice.pinned.len(),
+                max: self.regulation.speciality.count,
+            });
+        }
+        if let Some(unknown) = speciality_choice
+            .pinned
+            .iter()
+            .chain(&speciality_choice.candidates)
+            .find(|speciality| !self.available_specialities.contains(speciality))
+        {
+            return Err(PlanError::UnknownSpeciality {
+                speciality: unknown.clone(),
+            });
+        }
+        Ok(Plan {
+            speciality_choice,
+            ..self
+        })
+    }
+
+    /// Pins the supplementary subjects of the bachelor and the master
+    pub fn with_supplementary_choice(self, supplementary_choice: SupplementaryChoice) -> Plan {
+        Plan {
+            supplementary_choice,
+            ..self
+        }
+    }
+
+    /// Picked specialities, `None` until solved
+    pub fn specialties(&self) -> &[Option<Speciality>] {
+        &self.specialties
+    }
 
-        plan
+    pub fn semesters(&self) -> &[Semester] {
+        &self.semesters
+    }
+
+    /// Number of the first semester enrolled in the master, `None` until solved
+    pub fn master_start(&self) -> Option<i32> {
+        self.master_start
+    }
+
+    /// The supplementary subject of the degree, if the regulation asks for one
+    pub fn supplementary_subject(&self, degree: SemesterDegree) -> Option<&SupplementarySubject> {
+        match degree {
+            SemesterDegree::Bachelor => self.supplementary_subjects[0].as_ref(),
+            SemesterDegree::Master => self.supplementary_subjects[1].as_ref(),
+        }
+    }
+
+    /// ECTS of the degree earned in its supplementary subject in half ECTS
+    pub fn supplementary_half_ects(&self, degree: SemesterDegree) -> i32 {
+        self.semesters
+            .iter()
+            .flat_map(|semester| &semester.modules)
+            .filter(|module| module.subject.is_some() && !module.status.is_failed())
+            .filter(|module| match degree {
+                SemesterDegree::Bachelor => module.degree == Bachelor,
+                SemesterDegree::Master => matches!(module.degree, Degree::Master(_)),
+            })
+            .map(|module| module.half_ects)
+            .sum()
+    }
+
+    /// Master modules taken while still enrolled in the bachelor, these have to be registered
+    /// as taken early
+    pub fn early_master_modules(&self) -> Vec<&Module> {
+        let Some(master_start) = self.master_start else {
+            return vec![];
+        };
+        self.semesters
+            .iter()
+            .filter(|semester| semester.number < master_start)
+            .flat_map(|semester| &semester.modules)
+            .filter(|module| {
+                matches!(module.degree, Degree::Master(_)) && !module.status.is_failed()
+            })
+            .collect()
+    }
+
+    /// Recommendations of taken modules that were not finished in an earlier semester
+    pub fn violated_recommendations(&self) -> Vec<(&Module, &Requirement)> {
+        let finished_in: HashMap<_, _> = self
+            .semesters
+            .iter()
+            .enumerate()
+            .flat_map(|(index, semester)| {
+                semester
+                    .modules
+                    .iter()
+                    .filter(|module| !module.status.is_failed())
+                    .map(move |module| (module.identifier.as_str(), index + module.duration() - 1))
+            })
+            .collect();
+
+        let mut violated = vec![];
+        for (index, semester) in self.semesters.iter().enumerate() {
+            let taken_before = |identifier: &str| {
+                finished_in
+                    .get(identifier)
+                    .is_some_and(|finished| *finished < index)
+            };
+            for module in semester.modules.iter().filter(|module| !module.status.is_failed()) {
+                violated.extend(
+                    module
+                        .recommendations
+                        .iter()
+                        .filter(|recommendation| !recommendation.is_met(&taken_before))
+                        .map(|recommendation| (module, recommendation)),
+                );
+            }
+        }
+        violated
+    }
+
+    /// ECTS of modules counting toward the bachelor in half ECTS
+    pub fn bachelor_half_ects(&self) -> i32 {
+        self.semesters
+            .iter()
+            .flat_map(|semester| &semester.modules)
+            .filter(|module| module.degree == Bachelor && !module.status.is_failed())
+            .map(|module| module.half_ects)
+            .sum()
+    }
+
+    /// ECTS of modules counting toward the master in half ECTS
+    pub fn master_half_ects(&self) -> i32 {
+        self.semesters
+            .iter()
+            .flat_map(|semester| &semester.modules)
+            .filter(|module| {
+                matches!(module.degree, Degree::Master(_)) && !module.status.is_failed()
+            })
+            .map(|module| module.half_ects)
+            .sum()
+    }
+
+    /// Used modules that count toward neither degree
+    pub fn additional_modules(&self) -> Vec<&Module> {
+        self.semesters
+            .iter()
+            .flat_map(|semester| &semester.modules)
+            .filter(|module| module.degree == Degree::Additional && !module.status.is_failed())
+            .collect()
+    }
+
+    /// ECTS of modules counting toward neither degree in half ECTS
+    pub fn additional_half_ects(&self) -> i32 {
+        self.additional_modules()
+            .iter()
+            .map(|module| module.half_ects)
+            .sum()
+    }
+
+    /// Projected average grade of the degree from passed and target grades. Ungraded modules and
+    /// modules without a grade are left out.
+    pub fn average_grade(&self, degree: SemesterDegree) -> Option<Grade> {
+        let grades = self
+            .semesters
+            .iter()
+            .flat_map(|semester| &semester.modules)
+            .filter(|module| !module.status.is_failed() && !module.ungraded)
+            .filter(|module| match degree {
+                SemesterDegree::Bachelor => module.degree == Bachelor,
+                SemesterDegree::Master => matches!(module.degree, Degree::Master(_)),
+            })
+            .filter_map(|module| module.grade().map(|grade| (grade, module.half_ects)));
+        Grade::weighted_average(grades)
     }
 
-    pub fn check_basic(&self) {
+    /// Rejects root modules without the ECTS of the regulation
+    pub fn check_basic(&self) -> Result<(), PlanError> {
         let invalid_root_modules = self
             .modules
             .iter()
             .filter(|&module| {
                 matches!(module.module_type, ModuleType::Lecture { is_root: true })
-                    && module.half_ects != 12
+                    && module.half_ects != self.regulation.root_module_ects * 2
             })
+            .map(Module::label)
             .collect::<Vec<_>>();
-        assert_eq!(
-            invalid_root_modules,
-            Vec::<&Module>::new(),
-            "Stammmodule müssen 6 ECTS haben"
-        )
+        if !invalid_root_modules.is_empty() {
+            return Err(PlanError::RootModuleEcts {
+                modules: invalid_root_modules,
+                ects: self.regulation.root_module_ects,
+            });
+        }
+        Ok(())
     }
 
-    pub fn get_solutions(self) -> Vec<Plan> {
-        let context = Context::new(&Config::new());
-        let solver = Solver::new(&context);
+    /// Whether `requirement` is met by modules taken before `z3_module`
+    fn requirement_met<'ctx>(
+        context: &'ctx Context,
+        requirement: &Requirement,
+        module: &Module,
+        z3_module: &Z3Module<'ctx>,
+        z3_modules: &[Z3Module<'ctx>],
+    ) -> Result<Bool<'ctx>, PlanError> {
+        let all_met = |requirements: &[Requirement]| {
+            requirements
+                .iter()
+                .map(|requirement| {
+                    Self::requirement_met(context, requirement, module, z3_module, z3_modules)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        match requirement {
+            Requirement::Module(identifier) => {
+                let required_z3_module = z3_modules
+                    .iter()
+                    .find(|module| module.identifier == *identifier)
+                    .ok_or_else(|| PlanError::UnknownRequirement {
+                        module: module.label(),
+                        requirement: identifier.clone(),
+                    })?;
+                Ok(&required_z3_module.used & &z3_module.semester.gt(&required_z3_module.end()))
+            }
+            Requirement::AllOf { all_of } => {
+                let met = all_met(all_of)?;
+                Ok(Bool::and(context, &met.iter().collect::<Vec<_>>()))
+            }
+            Requirement::AnyOf { any_of } => {
+                let met = all_met(any_of)?;
+                Ok(Bool::or(context, &met.iter().collect::<Vec<_>>()))
+            }
+        }
+    }
 
+    fn encode<'ctx>(
+        &self,
+        context: &'ctx Context,
+        solver: &impl TrackedAssertions<'ctx>,
+    ) -> Result<Encoding<'ctx>, PlanError> {
         let semester_count = self.semesters.len();
-        let (degree_sort, degree_values, degree_testers) = SemesterDegree::z3_enum(&context);
-        let [bachelor, master] = &degree_values[..] else {
-            panic!("aaaa")
+        let (degree_sort, degree_values, degree_testers) = CountsToward::z3_enum(context);
+        let degree_error = || PlanError::Encoding {
+            message: "the degree sort needs exactly a bachelor, master and additional value"
+                .to_string(),
+        };
+        let [bachelor, master, additional] = &degree_values[..] else {
+            return Err(degree_error());
         };
         let bachelor = bachelor.apply(&[]);
         let master = master.apply(&[]);
+        let additional = additional.apply(&[]);
 
-        let [bachelor_tester, master_tester] = &degree_testers[..] else {
-            panic!("aaaa")
+        let [bachelor_tester, master_tester, additional_tester] = &degree_testers[..] else {
+            return Err(degree_error());
         };
 
+        // The constructors ensure there is at least one and that the choice only names these
+        let available_specialities = &self.available_specialities;
+        let choice = &self.speciality_choice;
         let (speciality_sort, speciality_values, _speciality_testers) =
-            Speciality::z3_enum(&context);
-        let speciality_values = speciality_values
+            Speciality::z3_enum(context, available_specialities);
+        let speciality_values: Vec<_> = available_specialities
             .iter()
-            .map(|val| val.apply(&[]).as_datatype().unwrap())
-            .collect::<Vec<_>>();
+            .cloned()
+            .zip(&speciality_values)
+            .map(|(speciality, val)| (speciality, val.apply(&[]).as_datatype().unwrap()))
+            .collect();
 
-        let first_specialty = Datatype::new_const(&context, "First specialty", &speciality_sort);
-        let second_specialty = Datatype::new_const(&context, "Second specialty", &speciality_sort);
+        let specialties: Vec<_> = (0..self.regulation.speciality.count)
+            .map(|index| {
+                Datatype::new_const(context, format!("Specialty {}", index + 1), &speciality_sort)
+            })
+            .collect();
+        if specialties.len() > 1 {
+            solver.assert_tracked(
+                &Datatype::distinct(context, &specialties.iter().collect::<Vec<_>>()),
+                Reason::DistinctSpecialties,
+            );
+        }
 
-        solver.assert_and_track(
-            &Datatype::distinct(&context, &[&first_specialty, &second_specialty]),
-            &Bool::new_const(&context, "Must be two specialties"),
-        );
+        let speciality_value = |speciality: &Speciality| {
+            let (_, value) = speciality_values
+                .iter()
+                .find(|(entry, _)| entry == speciality)
+                .unwrap();
+            value
+        };
+        for (specialty, pinned) in specialties.iter().zip(&choice.pinned) {
+            solver.assert_tracked(
+                &specialty._eq(speciality_value(pinned)),
+                Reason::PinnedSpeciality {
+                    speciality: pinned.clone(),
+                },
+            );
+        }
+        // Pinned specialities take the first slots, only the others are picked from the candidates
+        if !choice.candidates.is_empty() {
+            for (index, specialty) in specialties.iter().enumerate().skip(choice.pinned.len()) {
+                let is_candidate: Vec<_> = choice
+                    .candidates
+                    .iter()
+                    .map(|candidate| specialty._eq(speciality_value(candidate)))
+                    .collect();
+                let is_candidate: Vec<_> = is_candidate.iter().collect();
+                solver.assert_tracked(
+                    &Bool::or(context, &is_candidate),
+                    Reason::SpecialityCandidates { specialty: index },
+                );
+            }
+        }
+        // Excluded specialities the catalog does not declare can't be picked anyway
+        let excluded = choice
+            .excluded
+            .iter()
+            .filter(|excluded| speciality_values.iter().any(|(entry, _)| entry == *excluded));
+        for excluded in excluded {
+            let value = speciality_value(excluded);
+            let is_picked: Vec<_> = specialties
+                .iter()
+                .map(|specialty| specialty._eq(value))
+                .collect();
+            let is_picked: Vec<_> = is_picked.iter().collect();
+            solver.assert_tracked(
+                &!Bool::or(context, &is_picked),
+                Reason::ExcludedSpeciality {
+                    speciality: excluded.clone(),
+                },
+            );
+        }
 
-        let zero = Int::from_i64(&context, 0);
-        let one = Int::from_i64(&context, 1);
-        let semester_count_z3 = Int::from_i64(&context, semester_count as i64);
+        let zero = Int::from_i64(context, 0);
+        let one = Int::from_i64(context, 1);
+        let semester_count_z3 = Int::from_i64(context, semester_count as i64);
 
         let z3_modules: Vec<_> = self
             .modules
             .iter()
             .map(|module| {
                 Z3Module::from_module(
-                    &context,
+                    context,
                     module,
-                    &degree_sort,
-                    bachelor_tester,
+                    &DegreeSort {
+                        sort: &degree_sort,
+                        is_bachelor: bachelor_tester,
+                        is_additional: additional_tester,
+                    },
                     &speciality_sort,
                     &speciality_values,
-                    &solver,
+                    solver,
                 )
             })
             .collect();
         for z3_module in &z3_modules {
-            solver.assert_and_track(
-                &z3_module.semester.ge(&zero),
-                &Bool::new_const(
-                    &context,
-                    format!("Semester_number >= 0 [{}]", z3_module.identifier),
-                ),
-            );
-            solver.assert_and_track(
-                &z3_module.semester.lt(&semester_count_z3),
-                &Bool::new_const(
-                    &context,
-                    format!(
-                        "Semester_number < {} [{}]",
-                        semester_count, z3_module.identifier
-                    ),
-                ),
+            solver.assert_tracked(
+                &(z3_module.semester.ge(&zero) & z3_module.end().lt(&semester_count_z3)),
+                Reason::WithinPlan {
+                    module: z3_module.label.clone(),
+                    semester_count,
+                },
             );
 
-            if let Some((index, _)) = self.semesters.iter().enumerate().find(|(_, element)| {
-                element
-                    .modules
-                    .iter()
-                    .any(|module| module.identifier == z3_module.identifier)
-            }) {
-                solver.assert_and_track(
-                    &z3_module
+            if let Some((index, semester)) =
+                self.semesters.iter().enumerate().find(|(_, element)| {
+                    element.modules.iter().any(|module| {
+                        module.identifier == z3_module.identifier && !module.status.is_failed()
+                    })
+                })
+            {
+                solver.assert_tracked(
+                    &(z3_module
                         .semester
-                        ._eq(&Int::from_i64(&context, index as i64)),
-                    &Bool::new_const(
-                        &context,
-                        format!("Fixed semester number {} [{}]", index, z3_module.identifier),
-                    ),
-                );
-                solver.assert_and_track(
-                    &z3_module.used,
-                    &Bool::new_const(&context, format!("{} has to be used", z3_module.identifier)),
+                        ._eq(&Int::from_i64(context, index as i64))
+                        & &z3_module.used),
+                    Reason::Scheduled {
+                        module: z3_module.label.clone(),
+                        semester: semester.number,
+                    },
                 );
             }
         }
 
+        // Retakes of failed modules
+        for (index, semester) in self.semesters.iter().enumerate() {
+            let failed_modules = semester
+                .modules
+                .iter()
+                .filter_map(|module| match module.status {
+                    ModuleStatus::Failed { attempts_left } => Some((module, attempts_left)),
+                    _ => None,
+                });
+            for (module, attempts_left) in failed_modules {
+                let z3_module = z3_modules
+                    .iter()
+                    .find(|z3_module| z3_module.identifier == module.identifier)
+                    .ok_or_else(|| PlanError::Encoding {
+                        message: format!("{} is not part of the plan", module.label()),
+                    })?;
+                if attempts_left == Some(0) {
+                    solver.assert_tracked(
+                        &!&z3_module.used,
+                        Reason::NoAttemptsLeft {
+                            module: z3_module.label.clone(),
+                        },
+                    );
+                } else {
+                    let failed_in = Int::from_i64(context, index as i64);
+                    solver.assert_tracked(
+                        &(&z3_module.used & z3_module.semester.gt(&failed_in)),
+                        Reason::Retake {
+                            module: z3_module.label.clone(),
+                            semester: semester.number,
+                        },
+                    );
+                }
+            }
+        }
+
         // Max ects:
+        let mut semester_sums = vec![];
         for (semester_index, semester) in self.semesters.iter().enumerate() {
-            let mut semester_sum = Int::from_i64(&context, 0);
+            let mut semester_sum = Int::from_i64(context, 0);
             for z3_module in &z3_modules {
-                let counted = &z3_module.used
-                    & &z3_module
-                        .semester
-                        ._eq(&Int::from_i64(&context, semester_index as i64));
-                semester_sum += counted.ite(&z3_module.ects, &zero);
-            }
-
-            solver.assert_and_track(
-                &semester_sum.le(&Int::from_i64(&context, semester.ects.end as i64)),
-                &Bool::new_const(
-                    &context,
-                    format!(
-                        "Sum for semester {} <= {}",
-                        semester_index, semester.ects.end
-                    ),
-                ),
+                semester_sum += z3_module.ects_in(semester_index);
+            }
+
+            solver.assert_tracked(
+                &semester_sum.le(&Int::from_i64(context, semester.ects.end as i64)),
+                Reason::SemesterMaxEcts {
+                    semester: semester.number,
+                    half_ects: semester.ects.end,
+                },
             );
-            solver.assert_and_track(
-                &semester_sum.ge(&Int::from_i64(&context, semester.ects.start as i64)),
-                &Bool::new_const(
-                    &context,
-                    format!(
-                        "Sum for semester {} >= {}",
-                        semester_index, semester.ects.start
-                    ),
-                ),
+            solver.assert_tracked(
+                &semester_sum.ge(&Int::from_i64(context, semester.ects.start as i64)),
+                Reason::SemesterMinEcts {
+                    semester: semester.number,
+                    half_ects: semester.ects.start,
+                },
             );
+            semester_sums.push(semester_sum);
         }
 
         // Totals
-        let bachelor_min = 180 - 6 - 15 - 12;
-        let bachelor_max = Int::from_i64(&context, (bachelor_min + 4) * 2);
-        let bachelor_min = Int::from_i64(&context, bachelor_min * 2);
-
-        let master_min = 120 - 41;
-        let master_max = Int::from_i64(&context, (master_min + 4) * 2);
-        let master_min = Int::from_i64(&context, (master_min) * 2);
-
-        let mut bachelor_sum = Int::from_i64(&context, 0);
-        let mut master_sum = Int::from_i64(&context, 0);
+        let regulation = &self.regulation;
+        let tolerance = regulation.ects_tolerance;
+        // The thesis is a module of its own, only the other blocks are missing from the catalog
+        let bachelor_ects = regulation.bachelor.ects - regulation.bachelor.other_ects;
+        let bachelor_min_half_ects = bachelor_ects * 2;
+        let bachelor_max_half_ects = (bachelor_ects + tolerance) * 2;
+        let bachelor_max = Int::from_i64(context, bachelor_max_half_ects as i64);
+        let bachelor_min = Int::from_i64(context, bachelor_min_half_ects as i64);
+
+        let master_ects = regulation.master.common.ects - regulation.master.common.other_ects;
+        let master_min_half_ects = master_ects * 2;
+        let master_max_half_ects = (master_ects + tolerance) * 2;
+        let master_max = Int::from_i64(context, master_max_half_ects as i64);
+        let master_min = Int::from_i64(context, master_min_half_ects as i64);
+
+        let mut bachelor_sum = Int::from_i64(context, 0);
+        let mut master_sum = Int::from_i64(context, 0);
         for z3_module in &z3_modules {
             let is_bachelor = bachelor_tester
                 .apply(&[&z3_module.degree])
@@ -350,13 +1421,17 @@ impl Plan {
             master_sum += is_master.ite(&z3_module.ects, &zero);
         }
 
-        solver.assert_and_track(
+        solver.assert_tracked(
             &bachelor_sum.ge(&bachelor_min),
-            &Bool::new_const(&context, "Bachelor minimum ECTS"),
+            Reason::BachelorMinEcts {
+                half_ects: bachelor_min_half_ects,
+            },
         );
-        solver.assert_and_track(
+        solver.assert_tracked(
             &bachelor_sum.le(&bachelor_max),
-            &Bool::new_const(&context, "Bachelor maximum ECTS"),
+            Reason::BachelorMaxEcts {
+                half_ects: bachelor_max_half_ects,
+            },
         );
 
         // Stammmodule
@@ -365,8 +1440,8 @@ impl Plan {
             .iter()
             .filter(|module| matches!(module.module_type, ModuleType::Lecture { is_root: true }))
             .collect();
-        let mut bachelor_root_module_count = Int::from_i64(&context, 0);
-        let mut master_root_module_count = Int::from_i64(&context, 0);
+        let mut bachelor_root_module_count = Int::from_i64(context, 0);
+        let mut master_root_module_count = Int::from_i64(context, 0);
         for z3_module in z3_modules.iter().filter(|z3_module| {
             root_modules
                 .iter()
@@ -384,13 +1459,23 @@ impl Plan {
             master_root_module_count += is_master.ite(&one, &zero);
         }
 
-        solver.assert_and_track(
-            &bachelor_root_module_count.ge(&one),
-            &Bool::new_const(&context, "Bachelor root module count"),
+        solver.assert_tracked(
+            &bachelor_root_module_count.ge(&Int::from_i64(
+                context,
+                regulation.bachelor.root_modules as i64,
+            )),
+            Reason::BachelorRootModules {
+                count: regulation.bachelor.root_modules,
+            },
         );
-        solver.assert_and_track(
-            &master_root_module_count.ge(&Int::from_i64(&context, 4)),
-            &Bool::new_const(&context, "Master root module count"),
+        solver.assert_tracked(
+            &master_root_module_count.ge(&Int::from_i64(
+                context,
+                regulation.master.common.root_modules as i64,
+            )),
+            Reason::MasterRootModules {
+                count: regulation.master.common.root_modules,
+            },
         );
 
         // Master praktika
@@ -399,7 +1484,7 @@ impl Plan {
             .iter()
             .filter(|module| matches!(module.module_type, ModuleType::Lab))
             .collect();
-        let mut master_lab_module_count = Int::from_i64(&context, 0);
+        let mut master_lab_module_count = Int::from_i64(context, 0);
         for z3_module in z3_modules.iter().filter(|z3_module| {
             lab_modules
                 .iter()
@@ -410,9 +1495,14 @@ impl Plan {
             master_lab_module_count += is_master.ite(&z3_module.ects, &zero);
         }
 
-        solver.assert_and_track(
-            &master_lab_module_count.ge(&Int::from_i64(&context, 6 * 2)),
-            &Bool::new_const(&context, "Master lab module count"),
+        solver.assert_tracked(
+            &master_lab_module_count.ge(&Int::from_i64(
+                context,
+                regulation.master.lab_ects as i64 * 2,
+            )),
+            Reason::MasterLabEcts {
+                half_ects: regulation.master.lab_ects * 2,
+            },
         );
 
         // Master seminare
@@ -421,7 +1511,7 @@ impl Plan {
             .iter()
             .filter(|module| matches!(module.module_type, ModuleType::Seminar { is_pro: _ }))
             .collect();
-        let mut master_seminar_module_count = Int::from_i64(&context, 0);
+        let mut master_seminar_module_count = Int::from_i64(context, 0);
         for z3_module in z3_modules.iter().filter(|z3_module| {
             seminar_modules
                 .iter()
@@ -432,28 +1522,43 @@ impl Plan {
             master_seminar_module_count += is_master.ite(&z3_module.ects, &zero);
         }
 
-        solver.assert_and_track(
-            &master_seminar_module_count.ge(&Int::from_i64(&context, 2 * 3)),
-            &Bool::new_const(&context, "Master seminar module count"),
+        solver.assert_tracked(
+            &master_seminar_module_count.ge(&Int::from_i64(
+                context,
+                regulation.master.seminar_ects as i64 * 2,
+            )),
+            Reason::MasterSeminarEcts {
+                half_ects: regulation.master.seminar_ects * 2,
+            },
         );
 
         let lab_seminar_sum = master_seminar_module_count + master_lab_module_count;
-        solver.assert_and_track(
-            &lab_seminar_sum.ge(&Int::from_i64(&context, 2 * 3)),
-            &Bool::new_const(&context, "Master lab + seminar module count"),
+        solver.assert_tracked(
+            &lab_seminar_sum.ge(&Int::from_i64(
+                context,
+                regulation.master.lab_seminar_ects as i64 * 2,
+            )),
+            Reason::MasterLabSeminarEcts {
+                half_ects: regulation.master.lab_seminar_ects * 2,
+            },
         );
 
         // Master sum adjusted for not-counted seminars/labs
-        let overlap = Int::from_i64(&context, 18 * 2) - lab_seminar_sum;
+        let overlap = Int::from_i64(context, regulation.master.lab_seminar_cap_ects as i64 * 2)
+            - lab_seminar_sum;
         let overlap = overlap.ge(&one).ite(&overlap, &zero);
         master_sum -= overlap;
-        solver.assert_and_track(
+        solver.assert_tracked(
             &master_sum.le(&master_max),
-            &Bool::new_const(&context, "Master max ects"),
+            Reason::MasterMaxEcts {
+                half_ects: master_max_half_ects,
+            },
         );
-        solver.assert_and_track(
+        solver.assert_tracked(
             &master_sum.ge(&master_min),
-            &Bool::new_const(&context, "Master min ects"),
+            Reason::MasterMinEcts {
+                half_ects: master_min_half_ects,
+            },
         );
 
         // Ensure proper order
@@ -463,35 +1568,101 @@ impl Plan {
                 .iter()
                 .find(|module| module.identifier == z3_module.identifier)
                 .unwrap();
-            for &requirement in &module.requirements {
-                let required_z3_module = z3_modules
-                    .iter()
-                    .find(|module| module.identifier == requirement)
-                    .unwrap_or_else(|| panic!("Did not find identifier {}", requirement));
-
-                solver.assert_and_track(
-                    &z3_module.semester.gt(&required_z3_module.semester),
-                    &Bool::new_const(
-                        &context,
-                        format!(
-                            "{} is a requirement of {}_{} (semester)",
-                            required_z3_module.identifier, module.name, z3_module.identifier
-                        ),
-                    ),
+            for requirement in &module.requirements {
+                let met = Self::requirement_met(
+                    context,
+                    requirement,
+                    module,
+                    z3_module,
+                    &z3_modules,
+                )?;
+                solver.assert_tracked(
+                    &z3_module.used.implies(&met),
+                    Reason::Requirement {
+                        module: z3_module.label.clone(),
+                        required: requirement.to_string(),
+                    },
                 );
-                solver.assert_and_track(
-                    &z3_module.used.implies(&required_z3_module.used),
-                    &Bool::new_const(
-                        &context,
-                        format!(
-                            "{} is a requirement of {}_{} (usage)",
-                            required_z3_module.identifier, module.name, z3_module.identifier
-                        ),
-                    ),
+            }
+        }
+
+        // Co-requisites
+        for z3_module in &z3_modules {
+            let module = self
+                .modules
+                .iter()
+                .find(|module| module.identifier == z3_module.identifier)
+                .unwrap();
+            for corequisite in &module.corequisites {
+                let corequisite_z3_module = z3_modules
+                    .iter()
+                    .find(|other| other.identifier == corequisite.module)
+                    .ok_or_else(|| PlanError::UnknownRequirement {
+                        module: module.label(),
+                        requirement: corequisite.module.clone(),
+                    })?;
+                let in_time = match corequisite.timing {
+                    CorequisiteTiming::SameSemester => {
+                        z3_module.semester._eq(&corequisite_z3_module.semester)
+                    }
+                    CorequisiteTiming::SameOrEarlier => {
+                        corequisite_z3_module.semester.le(&z3_module.semester)
+                    }
+                };
+                solver.assert_tracked(
+                    &z3_module
+                        .used
+                        .implies(&(&corequisite_z3_module.used & in_time)),
+                    Reason::Corequisite {
+                        module: z3_module.label.clone(),
+                        corequisite: corequisite_z3_module.label.clone(),
+                        timing: corequisite.timing,
+                    },
                 );
             }
         }
 
+        // Mutually exclusive modules
+        let mut exclusion_groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for z3_module in &z3_modules {
+            for group in &z3_module.exclusion_groups {
+                exclusion_groups.entry(group).or_default().push(z3_module);
+            }
+        }
+        for (group, members) in exclusion_groups {
+            let mut used_count = zero.clone();
+            for member in &members {
+                used_count += member.used.ite(&one, &zero);
+            }
+            solver.assert_tracked(
+                &used_count.le(&one),
+                Reason::MutuallyExclusive {
+                    group: group.clone(),
+                    modules: members.iter().map(|member| member.label.clone()).collect(),
+                },
+            );
+        }
+
+        // Recommendations are only counted, optimizing keeps them low
+        let mut violated_recommendations = zero.clone();
+        for z3_module in &z3_modules {
+            let module = self
+                .modules
+                .iter()
+                .find(|module| module.identifier == z3_module.identifier)
+                .unwrap();
+            for recommendation in &module.recommendations {
+                let met = Self::requirement_met(
+                    context,
+                    recommendation,
+                    module,
+                    z3_module,
+                    &z3_modules,
+                )?;
+                violated_recommendations += (&z3_module.used & !met).ite(&one, &zero);
+            }
+        }
+
         // Ensure proseminar in bachelor
         let proseminars: Vec<Bool> = self
             .modules
@@ -512,10 +1683,69 @@ impl Plan {
             })
             .collect::<Vec<_>>();
         let proseminars = proseminars.iter().collect::<Vec<_>>();
-        solver.assert_and_track(
-            &Bool::or(&context, &proseminars[..]),
-            &Bool::new_const(&context, "Bachelor needs at least one proseminar"),
-        );
+        solver.assert_tracked(&Bool::or(context, &proseminars[..]), Reason::Proseminar);
+
+        // Exactly one thesis per degree requiring one, started once enough ECTS of that degree are
+        // passed
+        let theses: Vec<_> = z3_modules
+            .iter()
+            .filter(|z3_module| {
+                self.modules.iter().any(|module| {
+                    module.identifier == z3_module.identifier
+                        && module.module_type == ModuleType::Thesis
+                })
+            })
+            .collect();
+        let thesis_degrees = [
+            (
+                SemesterDegree::Bachelor,
+                bachelor_tester,
+                &regulation.bachelor.thesis,
+                regulation.bachelor.other_ects,
+            ),
+            (
+                SemesterDegree::Master,
+                master_tester,
+                &regulation.master.common.thesis,
+                regulation.master.common.other_ects,
+            ),
+        ];
+        for (degree, tester, thesis_regulation, other_ects) in thesis_degrees {
+            let Some(thesis_regulation) = thesis_regulation else {
+                continue;
+            };
+            let mut thesis_count = zero.clone();
+            for thesis in &theses {
+                let counts = tester.apply(&[&thesis.degree]).as_bool().unwrap() & &thesis.used;
+                thesis_count += counts.ite(&one, &zero);
+            }
+            solver.assert_tracked(&thesis_count._eq(&one), Reason::Thesis { degree });
+
+            // Blocks outside of the catalog are never scheduled, so they count as passed
+            let min_passed_half_ects = thesis_regulation.min_passed_ects * 2;
+            let min_catalog_half_ects = (thesis_regulation.min_passed_ects - other_ects) * 2;
+            if min_catalog_half_ects <= 0 {
+                continue;
+            }
+            for thesis in &theses {
+                let mut passed_before = zero.clone();
+                for z3_module in &z3_modules {
+                    let is_before = &z3_module.used
+                        & z3_module.degree._eq(&thesis.degree)
+                        & z3_module.end().lt(&thesis.semester);
+                    passed_before += is_before.ite(&z3_module.ects, &zero);
+                }
+                let counts = tester.apply(&[&thesis.degree]).as_bool().unwrap() & &thesis.used;
+                let min_passed = Int::from_i64(context, min_catalog_half_ects as i64);
+                solver.assert_tracked(
+                    &counts.implies(&passed_before.ge(&min_passed)),
+                    Reason::ThesisStart {
+                        module: thesis.label.clone(),
+                        half_ects: min_passed_half_ects,
+                    },
+                );
+            }
+        }
 
         // Ensure modules with fixed semester type are in a semester of that type
         let yearly_modules = self
@@ -542,26 +1772,211 @@ impl Plan {
                 let conditions: Vec<_> = semesters
                     .iter()
                     .map(|semester_index| {
-                        (module
+                        module
                             .semester
-                            ._eq(&Int::from_i64(&context, *semester_index as i64)))
+                            ._eq(&Int::from_i64(context, *semester_index as i64))
                     })
                     .collect();
                 let conditions: Vec<_> = conditions.iter().collect();
 
-                (
-                    Bool::or(&context, &conditions[..]),
-                    Bool::new_const(
-                        &context,
-                        format!(
-                            "Module {} has to be in one of {:?}",
-                            module.identifier, semesters
-                        ),
-                    ),
-                )
-            });
-        for (module, tracker) in yearly_modules {
-            solver.assert_and_track(&module, &tracker);
+                (
+                    Bool::or(context, &conditions[..]),
+                    Reason::SemesterType {
+                        module: module.label.clone(),
+                        semester_type: semester_type.clone(),
+                    },
+                )
+            });
+        for (module, reason) in yearly_modules {
+            solver.assert_tracked(&module, reason);
+        }
+
+        // Key qualifications
+        for (degree, tester, key_qualifications) in [
+            (
+                SemesterDegree::Bachelor,
+                bachelor_tester,
+                &regulation.bachelor.key_qualifications,
+            ),
+            (
+                SemesterDegree::Master,
+                master_tester,
+                &regulation.master.common.key_qualifications,
+            ),
+        ] {
+            let Some(key_qualifications) = key_qualifications else {
+                continue;
+            };
+            let mut key_qualification_sum = zero.clone();
+            for (z3_module, module) in z3_modules.iter().zip(&self.modules) {
+                if module.module_type != ModuleType::KeyQualification {
+                    continue;
+                }
+                let counts =
+                    tester.apply(&[&z3_module.degree]).as_bool().unwrap() & &z3_module.used;
+                key_qualification_sum += counts.ite(&z3_module.ects, &zero);
+            }
+            let min_half_ects = key_qualifications.min_ects * 2;
+            let max_half_ects = key_qualifications.max_ects * 2;
+            solver.assert_tracked(
+                &key_qualification_sum.ge(&Int::from_i64(context, min_half_ects as i64)),
+                Reason::KeyQualificationMinEcts {
+                    degree: degree.clone(),
+                    half_ects: min_half_ects,
+                },
+            );
+            solver.assert_tracked(
+                &key_qualification_sum.le(&Int::from_i64(context, max_half_ects as i64)),
+                Reason::KeyQualificationMaxEcts {
+                    degree,
+                    half_ects: max_half_ects,
+                },
+            );
+        }
+
+        // Supplementary subjects, modules of other subjects can only be additional achievements
+        let subject_sort = (!self.available_subjects.is_empty())
+            .then(|| SupplementarySubject::z3_enum(context, &self.available_subjects));
+        let subject_values: Vec<_> = match &subject_sort {
+            Some((_, values, _)) => self
+                .available_subjects
+                .iter()
+                .cloned()
+                .zip(values)
+                .map(|(subject, value)| (subject, value.apply(&[]).as_datatype().unwrap()))
+                .collect(),
+            None => vec![],
+        };
+        let supplementary_subjects = subject_sort.as_ref().map(|(sort, _, _)| {
+            [
+                Datatype::new_const(context, "Bachelor supplementary subject", sort),
+                Datatype::new_const(context, "Master supplementary subject", sort),
+            ]
+        });
+        // Subjects the catalog does not declare can never be picked
+        let is_subject = |subject: Option<&Datatype<'ctx>>, other: &SupplementarySubject| {
+            let value = subject_values
+                .iter()
+                .find(|(entry, _)| entry == other)
+                .map(|(_, value)| value);
+            match (subject, value) {
+                (Some(subject), Some(value)) => subject._eq(value),
+                _ => Bool::from_bool(context, false),
+            }
+        };
+        let supplementary_degrees = [
+            (
+                SemesterDegree::Bachelor,
+                bachelor_tester,
+                &regulation.bachelor.supplementary,
+                &self.supplementary_choice.bachelor,
+            ),
+            (
+                SemesterDegree::Master,
+                master_tester,
+                &regulation.master.common.supplementary,
+                &self.supplementary_choice.master,
+            ),
+        ];
+        for (index, (degree, tester, subject_regulation, pinned)) in
+            supplementary_degrees.into_iter().enumerate()
+        {
+            let subject = supplementary_subjects.as_ref().map(|subjects| &subjects[index]);
+            if let Some(pinned) = pinned {
+                solver.assert_tracked(
+                    &is_subject(subject, pinned),
+                    Reason::PinnedSupplementarySubject {
+                        degree: degree.clone(),
+                        subject: pinned.clone(),
+                    },
+                );
+            }
+
+            let mut subject_sum = zero.clone();
+            for (z3_module, module) in z3_modules.iter().zip(&self.modules) {
+                let Some(module_subject) = &module.subject else {
+                    continue;
+                };
+                let counts =
+                    tester.apply(&[&z3_module.degree]).as_bool().unwrap() & &z3_module.used;
+                solver.assert_tracked(
+                    &counts.implies(&is_subject(subject, module_subject)),
+                    Reason::SupplementarySubject {
+                        module: z3_module.label.clone(),
+                        degree: degree.clone(),
+                    },
+                );
+                subject_sum += counts.ite(&z3_module.ects, &zero);
+            }
+
+            if let Some(subject_regulation) = subject_regulation {
+                let min_half_ects = subject_regulation.min_ects * 2;
+                let max_half_ects = subject_regulation.max_ects * 2;
+                solver.assert_tracked(
+                    &subject_sum.ge(&Int::from_i64(context, min_half_ects as i64)),
+                    Reason::SupplementaryMinEcts {
+                        degree: degree.clone(),
+                        half_ects: min_half_ects,
+                    },
+                );
+                solver.assert_tracked(
+                    &subject_sum.le(&Int::from_i64(context, max_half_ects as i64)),
+                    Reason::SupplementaryMaxEcts {
+                        degree,
+                        half_ects: max_half_ects,
+                    },
+                );
+            }
+        }
+
+        // Enrollment switches from the bachelor to the master once, semesters allowing only one
+        // degree fix on which side of the switch they are
+        let master_start = Int::new_const(context, "Master start");
+        for (index, semester) in self.semesters.iter().enumerate() {
+            let index_z3 = Int::from_i64(context, index as i64);
+            let enrolled = if !semester.degrees.contains(&SemesterDegree::Master) {
+                Some((master_start.gt(&index_z3), SemesterDegree::Bachelor))
+            } else if !semester.degrees.contains(&SemesterDegree::Bachelor) {
+                Some((master_start.le(&index_z3), SemesterDegree::Master))
+            } else {
+                None
+            };
+            if let Some((enrolled, degree)) = enrolled {
+                solver.assert_tracked(
+                    &enrolled,
+                    Reason::Enrollment {
+                        semester: semester.number,
+                        degree,
+                    },
+                );
+            }
+        }
+
+        let mut early_master_sum = zero.clone();
+        for z3_module in &z3_modules {
+            let is_bachelor = bachelor_tester
+                .apply(&[&z3_module.degree])
+                .as_bool()
+                .unwrap();
+            let is_bachelor = is_bachelor & &z3_module.used;
+            solver.assert_tracked(
+                &is_bachelor.implies(&z3_module.end().lt(&master_start)),
+                Reason::BachelorBeforeMaster {
+                    module: z3_module.label.clone(),
+                },
+            );
+
+            let is_master = master_tester.apply(&[&z3_module.degree]).as_bool().unwrap();
+            let is_early = is_master & &z3_module.used & z3_module.semester.lt(&master_start);
+            early_master_sum += is_early.ite(&z3_module.ects, &zero);
+        }
+        if let Some(max_early_ects) = regulation.master.max_early_ects {
+            solver.assert_tracked(
+                &early_master_sum.le(&Int::from_i64(context, max_early_ects as i64 * 2)),
+                Reason::EarlyMasterEcts {
+                    half_ects: max_early_ects * 2,
+                },
+            );
         }
 
         // Check degree requirements
@@ -573,173 +1988,413 @@ impl Plan {
             };
 
             for z3_module in &z3_modules {
-                let is_semester = z3_module
-                    .semester
-                    ._eq(&Int::from_i64(&context, index as i64));
+                let is_semester = z3_module.covers(index);
                 let matches_degree = matcher.apply(&[&z3_module.degree]).as_bool().unwrap();
-                let condition = is_semester.implies(&matches_degree);
-                solver.assert_and_track(
+                // Additional achievements can be earned in any semester
+                let is_additional = additional_tester
+                    .apply(&[&z3_module.degree])
+                    .as_bool()
+                    .unwrap();
+                let condition = is_semester.implies(&(matches_degree | is_additional));
+                solver.assert_tracked(
                     &condition,
-                    &Bool::new_const(
-                        &context,
-                        format!(
-                            "If {} is in semester {index} it has to be {:?}",
-                            z3_module.identifier, semester.degrees[0]
-                        ),
-                    ),
+                    Reason::SemesterDegree {
+                        module: z3_module.label.clone(),
+                        semester: semester.number,
+                        degree: semester.degrees[0].clone(),
+                    },
                 );
             }
         }
 
-        let mut specialty_counts = [zero.clone(), zero.clone()];
-        let mut specialty_counts_no_root = [zero.clone(), zero.clone()];
         // Check specialty ects requirements
-        for (index, specialty) in [&first_specialty, &second_specialty]
-            .into_iter()
-            .enumerate()
-        {
-            let mut total_sum = Int::from_i64(&context, 0);
-            let mut without_root = Int::from_i64(&context, 0);
+        for (index, specialty) in specialties.iter().enumerate() {
+            let mut total_sum = Int::from_i64(context, 0);
+            let mut without_root = Int::from_i64(context, 0);
 
             for z3_module in &z3_modules {
+                let module = self
+                    .modules
+                    .iter()
+                    .find(|module| module.identifier == z3_module.identifier)
+                    .unwrap();
+                if !module.has_speciality() {
+                    continue;
+                }
+
                 let is_specialty = z3_module.associated_specialty._eq(specialty);
                 let is_master = z3_module.degree._eq(&master.as_datatype().unwrap());
                 let is_relevant = is_specialty & &z3_module.used & is_master;
                 let total_count = is_relevant.ite(&z3_module.ects, &zero);
                 total_sum += total_count;
 
-                let module = self
-                    .modules
-                    .iter()
-                    .find(|module| module.identifier == z3_module.identifier)
-                    .unwrap();
                 let is_root = matches!(module.module_type, ModuleType::Lecture { is_root: true });
                 let without_root_count = (is_relevant & !is_root).ite(&z3_module.ects, &zero);
                 without_root += without_root_count
             }
 
-            specialty_counts[index] = total_sum;
-            solver.assert_and_track(
-                &specialty_counts[index].ge(&Int::from_i64(&context, 15 * 2)),
-                &Bool::new_const(&context, "Specialty min ects (total)"),
+            solver.assert_tracked(
+                &total_sum.ge(&Int::from_i64(
+                    context,
+                    regulation.speciality.ects as i64 * 2,
+                )),
+                Reason::SpecialityEcts {
+                    specialty: index,
+                    half_ects: regulation.speciality.ects * 2,
+                },
             );
 
-            let (telematics_index, _) = Speciality::iter()
-                .enumerate()
-                .find(|(_, entry)| *entry == Telematics)
-                .unwrap();
-            let telematics_value = &speciality_values[telematics_index];
-            let is_telematics = specialty._eq(telematics_value);
-            let min_without_root = is_telematics.ite(
-                &Int::from_i64(&context, 8 * 2),
-                &Int::from_i64(&context, 10 * 2),
-            );
-            specialty_counts_no_root[index] = without_root;
-            solver.assert_and_track(
-                &specialty_counts_no_root[index].ge(&min_without_root),
-                &Bool::new_const(&context, "Specialty min ects (without root)"),
+            let min_without_root = speciality_values
+                .iter()
+                .filter_map(|(entry, value)| {
+                    let exceptions = &regulation.speciality.ects_without_root_exceptions;
+                    exceptions.get(entry).map(|ects| (value, *ects as i64))
+                })
+                .fold(
+                    Int::from_i64(context, regulation.speciality.ects_without_root as i64 * 2),
+                    |min_without_root, (value, ects)| {
+                        specialty
+                            ._eq(value)
+                            .ite(&Int::from_i64(context, ects * 2), &min_without_root)
+                    },
+                );
+            solver.assert_tracked(
+                &without_root.ge(&min_without_root),
+                Reason::SpecialityEctsWithoutRoot { specialty: index },
             );
         }
 
-        let mut solutions = vec![];
-        let mut solution_views = HashSet::new();
-
-        if solver.check() == SatResult::Unsat {
-            println!("Unsat :(");
-            dbg!(&solver.get_unsat_core());
-            return solutions;
-        }
+        Ok(Encoding {
+            z3_modules,
+            semester_sums,
+            violated_recommendations,
+            specialties,
+            speciality_values,
+            master_start,
+            supplementary_subjects,
+            subject_values,
+            bachelor: bachelor.as_datatype().unwrap(),
+            additional: additional.as_datatype().unwrap(),
+        })
+    }
 
-        while solver.check() == SatResult::Sat {
-            let model = solver.get_model().unwrap();
-            let semesters = self
-                .semesters
+    fn decode<'ctx>(
+        &self,
+        model: &Model<'ctx>,
+        encoding: &Encoding<'ctx>,
+    ) -> Result<Plan, PlanError> {
+        let missing = |what: &str, z3_module: &Z3Module| PlanError::Decoding {
+            message: format!("no valid {} for {}", what, z3_module.label),
+        };
+        let speciality = |specialty: &Datatype<'ctx>| {
+            let value = model.eval(specialty, true)?;
+            encoding
+                .speciality_values
                 .iter()
-                .enumerate()
-                .map(|(index, semester)| {
-                    let mut modules: Vec<_> = z3_modules
-                        .iter()
-                        .filter(|z3_module| {
-                            let is_used = model
-                                .eval(&z3_module.used, true)
-                                .unwrap()
-                                .as_bool()
-                                .unwrap();
-                            let semester = model
-                                .eval(&z3_module.semester, true)
-                                .unwrap()
-                                .as_i64()
-                                .unwrap();
-                            is_used && semester == index as i64
-                        })
-                        .map(|z3_module| {
-                            let is_bachelor = model
-                                .eval(&z3_module.degree, true)
-                                .unwrap()
-                                .eq(&bachelor.as_datatype().unwrap());
-
-                            let specialty = model
-                                .eval(&z3_module.associated_specialty, true)
-                                .unwrap()
-                                .to_string();
-                            let specialty = Speciality::from_str(&specialty).unwrap();
-
-                            let degree = if is_bachelor {
-                                Degree::Bachelor
-                            } else {
-                                Degree::Master(vec![specialty])
-                            };
-
-                            (z3_module, degree)
-                        })
-                        .map(|(z3_module, degree)| {
-                            let module = self
-                                .modules
-                                .iter()
-                                .find(|module| module.identifier == z3_module.identifier)
-                                .unwrap();
-                            let mut module = module.clone();
-                            module.degree = degree;
-
-                            module
-                        })
-                        .collect();
+                .find(|(_, entry)| *entry == value)
+                .map(|(speciality, _)| speciality.clone())
+        };
 
-                    let mut semester = semester.clone();
-                    semester.modules.clear();
-                    semester.modules.append(&mut modules);
+        let mut semesters: Vec<_> = self
+            .semesters
+            .iter()
+            .map(|semester| Semester {
+                modules: semester
+                    .modules
+                    .iter()
+                    .filter(|module| module.status.is_failed())
+                    .cloned()
+                    .collect(),
+                continued: vec![],
+                ..semester.clone()
+            })
+            .collect();
+        for z3_module in &encoding.z3_modules {
+            let is_used = model
+                .eval(&z3_module.used, true)
+                .and_then(|used| used.as_bool())
+                .ok_or_else(|| missing("usage", z3_module))?;
+            if !is_used {
+                continue;
+            }
 
-                    semester
-                })
-                .collect::<Vec<_>>();
+            let start = model
+                .eval(&z3_module.semester, true)
+                .and_then(|semester| semester.as_i64())
+                .and_then(|semester| usize::try_from(semester).ok())
+                .filter(|start| start + z3_module.ects_split.len() <= semesters.len())
+                .ok_or_else(|| missing("semester", z3_module))?;
 
-            let specialties = [&first_specialty, &second_specialty]
-                .map(|specialty| model.eval(specialty, true).unwrap().to_string())
-                .map(|name| Speciality::from_str(&name).ok());
+            let module = self
+                .modules
+                .iter()
+                .find(|module| module.identifier == z3_module.identifier)
+                .ok_or_else(|| missing("module", z3_module))?;
+            let degree = model
+                .eval(&z3_module.degree, true)
+                .ok_or_else(|| missing("degree", z3_module))?;
+            let degree = if degree == encoding.bachelor {
+                Degree::Bachelor
+            } else if degree == encoding.additional {
+                Degree::Additional
+            } else if !module.has_speciality() {
+                Degree::Master(vec![])
+            } else {
+                let specialty = speciality(&z3_module.associated_specialty)
+                    .ok_or_else(|| missing("speciality", z3_module))?;
+                Degree::Master(vec![specialty])
+            };
 
-            let plan = Plan {
-                semesters,
-                modules: vec![],
-                specialties,
+            let module = Module {
+                degree,
+                ..module.clone()
             };
+            for offset in 1..module.duration() {
+                semesters[start + offset]
+                    .continued
+                    .push((module.clone(), offset));
+            }
+            semesters[start].modules.push(module);
+        }
+
+        let specialties = encoding.specialties.iter().map(speciality).collect();
+
+        // Outside of the timeline the switch point makes no difference, so clamp it
+        let master_start = model
+            .eval(&encoding.master_start, true)
+            .and_then(|master_start| master_start.as_i64())
+            .ok_or_else(|| PlanError::Decoding {
+                message: "no valid master start".to_string(),
+            })?
+            .clamp(0, semesters.len() as i64);
+        let master_start = semesters
+            .get(master_start as usize)
+            .map(|semester| semester.number);
+
+        // Without a regulation for it the subject is arbitrary
+        let subject_regulations = [
+            &self.regulation.bachelor.supplementary,
+            &self.regulation.master.common.supplementary,
+        ];
+        let mut supplementary_subjects = [None, None];
+        for ((subject, regulation), decoded) in encoding
+            .supplementary_subjects
+            .iter()
+            .flatten()
+            .zip(subject_regulations)
+            .zip(&mut supplementary_subjects)
+        {
+            if regulation.is_some() {
+                let value = model.eval(subject, true);
+                *decoded = encoding
+                    .subject_values
+                    .iter()
+                    .find(|(_, entry)| Some(entry) == value.as_ref())
+                    .map(|(subject, _)| subject.clone());
+            }
+        }
+
+        Ok(Plan {
+            semesters,
+            modules: vec![],
+            specialties,
+            master_start,
+            supplementary_subjects,
+            available_subjects: self.available_subjects.clone(),
+            available_specialities: self.available_specialities.clone(),
+            speciality_choice: self.speciality_choice.clone(),
+            supplementary_choice: self.supplementary_choice.clone(),
+            regulation: self.regulation.clone(),
+        })
+    }
+
+    fn preference_score<'ctx>(context: &'ctx Context, encoding: &Encoding<'ctx>) -> Int<'ctx> {
+        let zero = Int::from_i64(context, 0);
+        let mut score = zero.clone();
+        for z3_module in &encoding.z3_modules {
+            let weight = Int::from_i64(context, z3_module.preference.score());
+            score += z3_module.used.ite(&weight, &zero);
+        }
+        score
+    }
+
+    fn objective_term<'ctx>(
+        &self,
+        context: &'ctx Context,
+        optimizer: &Optimize<'ctx>,
+        encoding: &Encoding<'ctx>,
+        objective: Objective,
+    ) -> Int<'ctx> {
+        let zero = Int::from_i64(context, 0);
+        match objective {
+            Objective::FewestSemesters => {
+                let last_semester = Int::new_const(context, "Last semester");
+                for z3_module in &encoding.z3_modules {
+                    optimizer.assert(&z3_module.used.implies(&z3_module.end().le(&last_semester)));
+                }
+                last_semester
+            }
+            Objective::MinimalOvershoot => {
+                // The required totals are fixed, so the smallest sum has the smallest overshoot
+                let mut total = zero.clone();
+                for z3_module in &encoding.z3_modules {
+                    total += z3_module.used.ite(&z3_module.ects, &zero);
+                }
+                total
+            }
+            Objective::EvenLoad => {
+                let max_load = Int::new_const(context, "Max semester load");
+                let min_load = Int::new_const(context, "Min semester load");
+                // Semesters that are already done can't be balanced anymore
+                let open_semesters = self
+                    .semesters
+                    .iter()
+                    .zip(&encoding.semester_sums)
+                    .filter(|(semester, _)| semester.modules.is_empty());
+                for (_, semester_sum) in open_semesters {
+                    optimizer.assert(&max_load.ge(semester_sum));
+                    optimizer.assert(&min_load.le(semester_sum));
+                }
+                max_load - min_load
+            }
+            Objective::PreferredModules => Self::preference_score(context, encoding),
+        }
+    }
+
+    /// Optimize has no unsat cores, so the tracking solver has to find the reasons
+    fn unsatisfiable(&self, options: &SolveOptions) -> PlanError {
+        match self.verify(options) {
+            Ok(Verdict::Unsatisfiable(reasons)) => PlanError::Unsatisfiable { reasons },
+            Ok(Verdict::Satisfiable | Verdict::Unknown) => PlanError::SolverUnknown {
+                reason: Some("only the optimizer found no plan".to_string()),
+            },
+            Err(error) => error,
+        }
+    }
+
+    fn optimize(
+        &self,
+        objective: Objective,
+        options: &SolveOptions,
+    ) -> Result<Plan, PlanError> {
+        let context = Context::new(&options.config());
+        let optimizer = Optimize::new(&context);
+        let encoding = self.encode(&context, &optimizer)?;
+
+        let term = self.objective_term(&context, &optimizer, &encoding, objective);
+        match objective {
+            Objective::PreferredModules => optimizer.maximize(&term),
+            _ => optimizer.minimize(&term),
+        }
+        // Objectives are lexicographic, so these only break ties
+        optimizer.minimize(&encoding.violated_recommendations);
+        if objective != Objective::PreferredModules {
+            optimizer.maximize(&Self::preference_score(&context, &encoding));
+        }
+
+        match optimizer.check(&[]) {
+            SatResult::Sat => {}
+            SatResult::Unsat => return Err(self.unsatisfiable(options)),
+            SatResult::Unknown => {
+                return Err(PlanError::SolverUnknown {
+                    reason: optimizer.get_reason_unknown(),
+                });
+            }
+        }
+        let model = optimizer.get_model().ok_or_else(|| PlanError::Decoding {
+            message: "the optimizer has no model".to_string(),
+        })?;
+        self.decode(&model, &encoding)
+    }
+
+    /// Enumerates plans satisfying all rules, the ones violating the fewest recommendations and
+    /// with the best preference score first. With an objective only the best plan by it is
+    /// returned.
+    ///
+    /// The first solve in a process sets the global z3 parameter `opt.elim_01` to `false`, which
+    /// z3 4.8 needs to optimize these plans correctly. It also applies to other z3 users in the
+    /// process.
+    pub fn get_solutions(self, options: &SolveOptions) -> Result<Vec<Plan>, PlanError> {
+        self.get_solutions_with_progress(options, |_| {})
+    }
+
+    /// Like [`Plan::get_solutions`], but calls `progress` with the number of distinct plans
+    /// found so far after every solution
+    pub fn get_solutions_with_progress(
+        self,
+        options: &SolveOptions,
+        mut progress: impl FnMut(usize),
+    ) -> Result<Vec<Plan>, PlanError> {
+        if let Some(objective) = options.objective {
+            return Ok(vec![self.optimize(objective, options)?]);
+        }
+
+        // The optimizer hands out the plans with the fewest violated recommendations and then the
+        // best preference score first
+        let context = Context::new(&options.config());
+        let solver = Optimize::new(&context);
+        let encoding = self.encode(&context, &solver)?;
+        if self.modules.iter().any(|module| !module.recommendations.is_empty()) {
+            solver.minimize(&encoding.violated_recommendations);
+        }
+        if self.modules.iter().any(|module| module.preference.score() != 0) {
+            solver.maximize(&Self::preference_score(&context, &encoding));
+        }
+
+        let mut solutions = vec![];
+        let mut solution_views = HashSet::new();
+
+        match solver.check(&[]) {
+            SatResult::Sat => {}
+            SatResult::Unsat => return Err(self.unsatisfiable(options)),
+            SatResult::Unknown => {
+                return Err(PlanError::SolverUnknown {
+                    reason: solver.get_reason_unknown(),
+                });
+            }
+        }
+
+        while options.limit.is_none_or(|limit| solutions.len() < limit)
+            && solver.check(&[]) == SatResult::Sat
+        {
+            let model = solver.get_model().ok_or_else(|| PlanError::Decoding {
+                message: "the solver has no model".to_string(),
+            })?;
+            let plan = self.decode(&model, &encoding)?;
             solutions.push(plan.clone());
             let view = PlanModuleView(plan);
             solution_views.insert(view);
-            print!("\rFound {} solutions so far ^C to cancel search and show them all", solution_views.len());
+            progress(solution_views.len());
 
-            let differences: Vec<_> = z3_modules
+            let differences = encoding.z3_modules
                 .iter()
-                .map(|module| &module.used)
-                .map(|property| !property._eq(&model.eval(property, true).unwrap()))
-                .collect();
+                .map(|module| {
+                    model
+                        .eval(&module.used, true)
+                        .map(|value| !module.used._eq(&value))
+                        .ok_or_else(|| PlanError::Decoding {
+                            message: format!("no valid usage for {}", module.label),
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
             let differences: Vec<_> = differences.iter().collect();
-            solver.assert_and_track(
-                &Bool::or(&context, differences.as_slice()),
-                &Bool::new_const(&context, "Ensure new solution"),
-            )
+            solver.assert(&Bool::or(&context, differences.as_slice()))
         }
-        println!();
-        solutions
+        Ok(solutions)
+    }
+
+    /// Checks whether any plan satisfies all rules, explaining why not if none does. Like
+    /// [`Plan::get_solutions`] this sets the global z3 parameter `opt.elim_01` to `false` once.
+    pub fn verify(&self, options: &SolveOptions) -> Result<Verdict, PlanError> {
+        let context = Context::new(&options.config());
+        let solver = TrackingSolver::new(&context);
+        self.encode(&context, &solver)?;
+
+        Ok(match solver.check() {
+            SatResult::Sat => Verdict::Satisfiable,
+            SatResult::Unknown => Verdict::Unknown,
+            SatResult::Unsat => Verdict::Unsatisfiable(solver.unsat_reasons()),
+        })
     }
 }
 
@@ -748,43 +2403,79 @@ impl Display for Plan {
         for semester in &self.semesters {
             let _ = writeln!(f, "{}", semester);
         }
+        let specialties: Vec<_> = self
+            .specialties
+            .iter()
+            .map(|specialty| {
+                specialty
+                    .as_ref()
+                    .map_or("unknown".to_string(), Speciality::to_string)
+            })
+            .collect();
+        let _ = writeln!(f, "\nSpecialties: {}", specialties.join(", "));
+        for (module, recommendation) in self.violated_recommendations() {
+            let _ = writeln!(
+                f,
+                "Recommendation {} for {} is not met",
+                recommendation,
+                module.label()
+            );
+        }
+        let bachelor_sum = f64::from(self.bachelor_half_ects()) / 2.0;
+        let master_sum = f64::from(self.master_half_ects()) / 2.0;
         let _ = writeln!(
             f,
-            "\nSpecialties: {}, {}",
-            self.specialties[0]
-                .map(|specialty| specialty.to_string())
-                .or_else(|| Some("unknown".to_string()))
-                .unwrap(),
-            self.specialties[1]
-                .map(|specialty| specialty.to_string())
-                .or_else(|| Some("unknown".to_string()))
-                .unwrap()
+            "ECTS Sum Bachelor {} Master: {}",
+            bachelor_sum, master_sum
         );
-        let bachelor_sum: i32 = self
-            .semesters
-            .iter()
-            .flat_map(|semester| &semester.modules)
-            .filter(|module| module.degree == Bachelor)
-            .map(|module| module.half_ects)
-            .sum();
-        let bachelor_sum = f64::from(bachelor_sum) / 2.0;
-        let master_sum: i32 = self
-            .semesters
-            .iter()
-            .flat_map(|semester| &semester.modules)
-            .filter(|module| matches!(module.degree, Degree::Master(_)))
-            .map(|module| module.half_ects)
-            .sum();
-        let master_sum = f64::from(master_sum) / 2.0;
+        for degree in [SemesterDegree::Bachelor, SemesterDegree::Master] {
+            if let Some(subject) = self.supplementary_subject(degree.clone()) {
+                let _ = writeln!(
+                    f,
+                    "Supplementary subject {:?}: {} with {} ECTS",
+                    degree.clone(),
+                    subject,
+                    f64::from(self.supplementary_half_ects(degree)) / 2.0
+                );
+            }
+        }
+        if let Some(master_start) = self.master_start {
+            let _ = writeln!(f, "Enrolled in the master from semester {}", master_start);
+        }
+        let early_master_modules: Vec<_> = self
+            .early_master_modules()
+            .into_iter()
+            .map(Module::label)
+            .collect();
+        if !early_master_modules.is_empty() {
+            let _ = writeln!(
+                f,
+                "Taken early for the master: {}",
+                early_master_modules.join(", ")
+            );
+        }
+        let additional_modules: Vec<_> = self
+            .additional_modules()
+            .into_iter()
+            .map(Module::label)
+            .collect();
+        if !additional_modules.is_empty() {
+            let _ = writeln!(f, "Additional achievements: {}", additional_modules.join(", "));
+        }
+        let average = |degree| {
+            self.average_grade(degree)
+                .map(|grade| grade.to_string())
+                .unwrap_or_else(|| "unknown".to_string())
+        };
         write!(
             f,
-            "ECTS Sum Bachelor {} Master: {}",
-            bachelor_sum, master_sum
+            "Average grade Bachelor {} Master: {}",
+            average(SemesterDegree::Bachelor),
+            average(SemesterDegree::Master)
         )
     }
 }
 
-#[derive(Hash)]
 pub struct PlanModuleView(pub Plan);
 
 impl PlanModuleView {
@@ -809,6 +2500,14 @@ impl PartialEq for PlanModuleView {
 
 impl Eq for PlanModuleView {}
 
+impl Hash for PlanModuleView {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut module_list: Vec<_> = self.get_module_set().into_iter().collect();
+        module_list.sort();
+        module_list.hash(state);
+    }
+}
+
 impl Ord for PlanModuleView {
     fn cmp(&self, other: &Self) -> Ordering {
         let mut module_list: Vec<_> = self.get_module_set().iter().copied().collect();
@@ -825,3 +2524,392 @@ impl PartialOrd for PlanModuleView {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regulation::ThesisRegulation;
+
+    fn module(identifier: &str) -> Requirement {
+        Requirement::Module(identifier.to_string())
+    }
+
+    #[derive(Deserialize)]
+    struct Requirements {
+        requirements: Vec<Requirement>,
+    }
+
+    /// Rules that only ask for a proseminar, tests raise the ones they are about
+    const SOLVER_REGULATION: &str = r#"
+        ects_tolerance = 100
+        root_module_ects = 6
+
+        [bachelor]
+        ects = 3
+        root_modules = 0
+
+        [master]
+        ects = 0
+        root_modules = 0
+        lab_ects = 0
+        seminar_ects = 0
+        lab_seminar_ects = 0
+        lab_seminar_cap_ects = 0
+
+        [speciality]
+        count = 2
+        ects = 0
+        ects_without_root = 0
+    "#;
+
+    fn solver_regulation() -> Regulation {
+        toml::from_str(SOLVER_REGULATION).unwrap()
+    }
+
+    fn solver_catalog() -> Catalog {
+        Catalog {
+            specialities: ["Algorithms", "Security", "Theoretics"].map(Speciality::new).to_vec(),
+            supplementary_subjects: vec![],
+            modules: vec![],
+        }
+    }
+
+    fn proseminar() -> Module {
+        Module::new("pro", "Proseminar", ModuleType::Seminar { is_pro: true }, 6, Bachelor)
+    }
+
+    fn timeline(degrees: &[&[SemesterDegree]]) -> Vec<Semester> {
+        degrees
+            .iter()
+            .zip(1..)
+            .map(|(degrees, number)| {
+                let semester_type = if number % 2 == 1 {
+                    SemesterType::Winter
+                } else {
+                    SemesterType::Summer
+                };
+                Semester::new(number, semester_type, degrees.to_vec(), 0..60)
+            })
+            .collect()
+    }
+
+    fn verdict(plan: Result<Plan, PlanError>) -> Vec<Reason> {
+        match plan.unwrap().verify(&SolveOptions::default()).unwrap() {
+            Verdict::Satisfiable => vec![],
+            Verdict::Unsatisfiable(reasons) => reasons,
+            Verdict::Unknown => panic!("the solver could not decide"),
+        }
+    }
+
+    fn speciality_choice(
+        pinned: &[&str],
+        candidates: &[&str],
+        excluded: &[&str],
+    ) -> SpecialityChoice {
+        let specialities = |names: &[&str]| names.iter().copied().map(Speciality::new).collect();
+        SpecialityChoice {
+            pinned: specialities(pinned),
+            candidates: specialities(candidates),
+            excluded: specialities(excluded),
+        }
+    }
+
+    #[test]
+    fn picks_the_unpinned_speciality_from_the_candidates() {
+        let semesters = timeline(&[&[SemesterDegree::Bachelor]]);
+        let plan = |choice: SpecialityChoice| {
+            Plan::from_semesters_with_modules(
+                &semesters,
+                &[proseminar()],
+                &solver_catalog(),
+                &solver_regulation(),
+            )
+            .and_then(|plan| plan.with_speciality_choice(choice))
+        };
+
+        let choice = speciality_choice(&["Theoretics"], &["Algorithms", "Security"], &[]);
+        assert_eq!(verdict(plan(choice)), vec![]);
+
+        let choice = speciality_choice(&["Theoretics"], &["Algorithms"], &["Algorithms"]);
+        let reasons = verdict(plan(choice));
+        assert!(reasons.contains(&Reason::SpecialityCandidates { specialty: 1 }));
+        assert!(!reasons.contains(&Reason::SpecialityCandidates { specialty: 0 }));
+    }
+
+    fn solver_plan(
+        semesters: &[Semester],
+        modules: Vec<Module>,
+        regulation: &Regulation,
+    ) -> Result<Plan, PlanError> {
+        let modules: Vec<_> = std::iter::once(proseminar()).chain(modules).collect();
+        Plan::from_semesters_with_modules(semesters, &modules, &solver_catalog(), regulation)
+    }
+
+    /// A lecture of 3 ECTS that has to be used
+    fn must(identifier: &str, degree: Degree) -> Module {
+        let lecture = ModuleType::Lecture { is_root: false };
+        Module::new(identifier, identifier, lecture, 6, degree).with_preference(Preference::Must)
+    }
+
+    #[test]
+    fn switches_enrollment_to_the_master_only_once() {
+        use SemesterDegree::{Bachelor, Master};
+        let regulation = solver_regulation();
+        let sat = timeline(&[&[Bachelor], &[Bachelor, Master], &[Master]]);
+        assert_eq!(verdict(solver_plan(&sat, vec![], &regulation)), vec![]);
+
+        let unsat = timeline(&[&[Bachelor], &[Master], &[Bachelor]]);
+        let reasons = verdict(solver_plan(&unsat, vec![], &regulation));
+        assert!(reasons.contains(&Reason::Enrollment {
+            semester: 2,
+            degree: Master
+        }));
+        assert!(reasons.contains(&Reason::Enrollment {
+            semester: 3,
+            degree: Bachelor
+        }));
+    }
+
+    #[test]
+    fn caps_master_ects_taken_early() {
+        let semesters = timeline(&[&[SemesterDegree::Bachelor, SemesterDegree::Master]]);
+        let early = || vec![must("early", Degree::Master(vec![Speciality::new("Algorithms")]))];
+        let plan = |max_early_ects: i32| {
+            // Counting the module toward the master keeps it from being moved to the additional
+            // modules
+            let mut regulation = solver_regulation();
+            regulation.master.common.ects = 3;
+            regulation.master.max_early_ects = Some(max_early_ects);
+            solver_plan(&semesters, early(), &regulation)
+        };
+        assert_eq!(verdict(plan(3)), vec![]);
+        assert!(verdict(plan(2)).contains(&Reason::EarlyMasterEcts { half_ects: 4 }));
+    }
+
+    #[test]
+    fn starts_the_thesis_after_enough_passed_ects() {
+        let bachelor_semester: &[SemesterDegree] = &[SemesterDegree::Bachelor];
+        let mut regulation = solver_regulation();
+        regulation.bachelor.thesis = Some(ThesisRegulation { min_passed_ects: 3 });
+        let thesis = || {
+            vec![Module::new("thesis", "Thesis", ModuleType::Thesis, 24, Bachelor)]
+        };
+        let two_semesters = timeline(&[bachelor_semester, bachelor_semester]);
+        assert_eq!(verdict(solver_plan(&two_semesters, thesis(), &regulation)), vec![]);
+
+        let one_semester = timeline(&[bachelor_semester]);
+        let reasons = verdict(solver_plan(&one_semester, thesis(), &regulation));
+        assert!(reasons.contains(&Reason::ThesisStart {
+            module: thesis()[0].label(),
+            half_ects: 6,
+        }));
+    }
+
+    #[test]
+    fn takes_corequisites_in_time() {
+        let bachelor_semester: &[SemesterDegree] = &[SemesterDegree::Bachelor];
+        let semesters = timeline(&[bachelor_semester, bachelor_semester]);
+        let regulation = solver_regulation();
+        let modules = |timing: CorequisiteTiming| {
+            vec![
+                must("lab", Bachelor)
+                    .with_semesters(vec![SemesterType::Summer])
+                    .with_corequisites(vec![Corequisite {
+                        module: "lecture".to_string(),
+                        timing,
+                    }]),
+                must("lecture", Bachelor).with_semesters(vec![SemesterType::Winter]),
+            ]
+        };
+
+        let earlier = modules(CorequisiteTiming::SameOrEarlier);
+        assert_eq!(verdict(solver_plan(&semesters, earlier, &regulation)), vec![]);
+
+        let same = modules(CorequisiteTiming::SameSemester);
+        let labels: Vec<_> = same.iter().map(Module::label).collect();
+        let reasons = verdict(solver_plan(&semesters, same, &regulation));
+        assert!(reasons.contains(&Reason::Corequisite {
+            module: labels[0].clone(),
+            corequisite: labels[1].clone(),
+            timing: CorequisiteTiming::SameSemester,
+        }));
+    }
+
+    #[test]
+    fn uses_at_most_one_module_of_an_exclusion_group() {
+        let semesters = timeline(&[&[SemesterDegree::Bachelor]]);
+        let regulation = solver_regulation();
+        let grouped = |identifier: &str| {
+            must(identifier, Bachelor).with_exclusion_groups(vec!["group".to_string()])
+        };
+        let modules = vec![grouped("a"), grouped("b")];
+        let labels: Vec<_> = modules.iter().map(Module::label).collect();
+        let reasons = verdict(solver_plan(&semesters, modules, &regulation));
+        assert!(reasons.contains(&Reason::MutuallyExclusive {
+            group: "group".to_string(),
+            modules: labels,
+        }));
+    }
+
+    #[test]
+    fn retakes_failed_modules_in_a_later_semester() {
+        let bachelor_semester: &[SemesterDegree] = &[SemesterDegree::Bachelor];
+        let regulation = solver_regulation();
+        let failed = Module::new("failed", "Failed", ModuleType::Lab, 6, Bachelor)
+            .with_status(ModuleStatus::Failed { attempts_left: None });
+        let with_failed = |count: usize| {
+            let mut semesters = timeline(&vec![bachelor_semester; count]);
+            semesters[0] = semesters[0].clone().with_modules(vec![failed.clone()]);
+            semesters
+        };
+
+        assert_eq!(verdict(solver_plan(&with_failed(2), vec![], &regulation)), vec![]);
+        let reasons = verdict(solver_plan(&with_failed(1), vec![], &regulation));
+        assert!(reasons.contains(&Reason::Retake {
+            module: failed.label(),
+            semester: 1,
+        }));
+    }
+
+    #[test]
+    fn prints_the_specialities_of_a_module_by_name() {
+        let degree = Degree::Master(["Algorithms", "Security"].map(Speciality::new).to_vec());
+        let module = Module::new("M-1", "Krypto", ModuleType::Lab, 12, degree);
+        assert_eq!(
+            module.to_string(),
+            "Praktikum Krypto [M-1] as Master([Algorithms, Security]) with 6 ECTS"
+        );
+    }
+
+    #[test]
+    fn counts_the_share_of_multi_semester_modules() {
+        let lecture = ModuleType::Lecture { is_root: false };
+        let started = Module::new("A", "A", lecture.clone(), 10, Bachelor)
+            .with_half_ects_per_semester(vec![4, 6]);
+        let failed = Module::new("B", "B", lecture.clone(), 10, Bachelor)
+            .with_status(ModuleStatus::Failed { attempts_left: None });
+        let continued = Module::new("C", "C", lecture, 10, Bachelor)
+            .with_half_ects_per_semester(vec![2, 8]);
+        let semester = Semester {
+            continued: vec![(continued, 1)],
+            ..Semester::new(2, SemesterType::Summer, vec![SemesterDegree::Bachelor], 0..60)
+                .with_modules(vec![started, failed])
+        };
+        assert_eq!(semester.half_ects(), 4 + 8);
+    }
+
+    fn grade(grade: f64) -> Grade {
+        Grade::try_from(grade).unwrap()
+    }
+
+    #[test]
+    fn cuts_off_the_average_after_the_first_decimal() {
+        let average = Grade::weighted_average([(grade(1.0), 10), (grade(1.3), 10)]);
+        assert_eq!(average, Some(grade(1.1)));
+        let average = Grade::weighted_average([(grade(1.7), 10), (grade(2.0), 10)]);
+        assert_eq!(average, Some(grade(1.8)));
+    }
+
+    #[test]
+    fn weights_grades_by_ects() {
+        let average = Grade::weighted_average([(grade(1.0), 30), (grade(4.0), 10)]);
+        assert_eq!(average, Some(grade(1.7)));
+        assert_eq!(Grade::weighted_average([]), None);
+    }
+
+    #[test]
+    fn leaves_ungraded_failed_and_other_modules_out_of_the_average() {
+        let regulation = toml::from_str(include_str!("../regulation.toml")).unwrap();
+        let lecture = ModuleType::Lecture { is_root: false };
+        let module = |identifier: &str, degree: Degree| {
+            Module::new(identifier, identifier, lecture.clone(), 10, degree)
+        };
+        let modules = vec![
+            module("passed", Bachelor).with_status(ModuleStatus::Passed {
+                grade: Some(grade(2.0)),
+            }),
+            module("target", Bachelor).with_target_grade(grade(3.0)),
+            module("ungraded", Bachelor)
+                .with_ungraded(true)
+                .with_target_grade(grade(1.0)),
+            module("failed", Bachelor)
+                .with_status(ModuleStatus::Failed { attempts_left: None })
+                .with_target_grade(grade(1.0)),
+            module("unknown", Bachelor),
+            module("master", Degree::Master(vec![])).with_target_grade(grade(1.0)),
+        ];
+        let semesters =
+            [Semester::new(1, SemesterType::Winter, vec![], 0..60).with_modules(modules)];
+        let catalog = Catalog {
+            specialities: vec![Speciality::new("Algorithms")],
+            supplementary_subjects: vec![],
+            modules: vec![],
+        };
+        let plan =
+            Plan::from_semesters_with_modules(&semesters, &[], &catalog, &regulation).unwrap();
+        assert_eq!(plan.average_grade(SemesterDegree::Bachelor), Some(grade(2.5)));
+        assert_eq!(plan.average_grade(SemesterDegree::Master), Some(grade(1.0)));
+    }
+
+    #[test]
+    fn deserializes_nested_requirements() {
+        let parsed: Requirements = toml::from_str(
+            r#"requirements = ["A", { any_of = ["B", { all_of = ["C", "D"] }] }]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.requirements,
+            vec![
+                module("A"),
+                Requirement::AnyOf {
+                    any_of: vec![
+                        module("B"),
+                        Requirement::AllOf {
+                            all_of: vec![module("C"), module("D")],
+                        },
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_requirement_keys() {
+        let parsed = toml::from_str::<Requirements>(r#"requirements = [{ one_of = ["A"] }]"#);
+        assert!(parsed.is_err());
+    }
+
+    #[test]
+    fn evaluates_requirements() {
+        let requirement = Requirement::AllOf {
+            all_of: vec![
+                module("A"),
+                Requirement::AnyOf {
+                    any_of: vec![module("B"), module("C")],
+                },
+            ],
+        };
+        let taken = |taken: &'static [&'static str]| {
+            move |identifier: &str| taken.contains(&identifier)
+        };
+        assert!(requirement.is_met(&taken(&["A", "C"])));
+        assert!(!requirement.is_met(&taken(&["A"])));
+        assert!(!requirement.is_met(&taken(&["B", "C"])));
+    }
+
+    #[test]
+    fn only_single_alternatives_are_mandatory() {
+        let requirement = Requirement::AllOf {
+            all_of: vec![
+                module("A"),
+                Requirement::AnyOf {
+                    any_of: vec![module("B")],
+                },
+                Requirement::AnyOf {
+                    any_of: vec![module("C"), module("D")],
+                },
+            ],
+        };
+        assert_eq!(requirement.mandatory_identifiers(), vec!["A", "B"]);
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::catalog::Catalog;
+use crate::error::{read_toml, LoadError};
+use crate::model::{
+    Degree, Grade, Module, ModuleStatus, Objective, Preference, Semester, SemesterDegree,
+    SemesterType, SpecialityChoice, SupplementaryChoice,
+};
+
+/// A student's plan file: the timeline with the modules taken so far and the wishes for the rest
+#[derive(Debug, Deserialize)]
+pub struct PlanFile {
+    /// Semesters of the timeline, written as `[[semester]]` tables
+    #[serde(rename = "semester", default)]
+    pub semesters: Vec<SemesterEntry>,
+    /// What to optimize for unless the command line overrides it
+    pub objective: Option<Objective>,
+    /// Preferences by module identifier, overriding the ones from the catalog
+    #[serde(default)]
+    pub preferences: BTreeMap<String, Preference>,
+    /// Grades aimed for in modules that are not passed yet, by module identifier
+    #[serde(default)]
+    pub target_grades: BTreeMap<String, Grade>,
+    /// Modules declared as additional achievements that count toward neither degree
+    #[serde(default)]
+    pub additional: Vec<String>,
+    /// Restrictions on the specialities the solver may pick
+    #[serde(default)]
+    pub specialities: SpecialityChoice,
+    /// Supplementary subjects pinned by the student
+    #[serde(default)]
+    pub supplementary_subjects: SupplementaryChoice,
+}
+
+/// A semester of the plan file
+#[derive(Debug, Deserialize)]
+pub struct SemesterEntry {
+    pub number: i32,
+    pub semester_type: SemesterType,
+    /// Degrees the student can be enrolled in during the semester
+    pub degrees: Vec<SemesterDegree>,
+    /// Least and most half ECTS the semester may hold
+    pub half_ects: EctsRange,
+    /// Identifiers of the modules taken in the semester
+    #[serde(default)]
+    pub modules: Vec<String>,
+    /// Status of the listed modules by identifier, modules without one are planned
+    #[serde(default)]
+    pub status: BTreeMap<String, ModuleStatus>,
+}
+
+/// Half ECTS a semester may hold
+#[derive(Debug, Deserialize)]
+pub struct EctsRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl PlanFile {
+    /// Reads a plan file from TOML, `check_choices` tests it against the catalog
+    pub fn from_file(path: impl AsRef<Path>) -> Result<PlanFile, LoadError> {
+        read_toml(path.as_ref())
+    }
+
+    /// Rejects modules, specialities and supplementary subjects the catalog does not declare
+    pub fn check_choices(&self, catalog: &Catalog) -> Result<(), LoadError> {
+        let overrides = self
+            .preferences
+            .keys()
+            .map(|identifier| (identifier, "preferences"))
+            .chain(self.target_grades.keys().map(|identifier| (identifier, "target_grades")))
+            .chain(self.additional.iter().map(|identifier| (identifier, "additional")));
+        for (identifier, setting) in overrides {
+            if catalog.find(identifier).is_none() {
+                return Err(LoadError::UnknownOverride {
+                    identifier: identifier.clone(),
+                    setting,
+                });
+            }
+        }
+        let choice = &self.specialities;
+        if let Some(speciality) = choice
+            .pinned
+            .iter()
+            .chain(&choice.candidates)
+            .chain(&choice.excluded)
+            .find(|speciality| !catalog.specialities.contains(speciality))
+        {
+            return Err(LoadError::UnknownSpeciality {
+                speciality: speciality.clone(),
+            });
+        }
+        let subjects = &self.supplementary_subjects;
+        if let Some(subject) = subjects
+            .bachelor
+            .iter()
+            .chain(&subjects.master)
+            .find(|subject| !catalog.supplementary_subjects.contains(subject))
+        {
+            return Err(LoadError::UnknownSubject {
+                subject: subject.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Semesters of the timeline with their modules looked up in the catalog and the overrides
+    /// of the plan file applied
+    pub fn semesters(&self, catalog: &Catalog) -> Result<Vec<Semester>, LoadError> {
+        self.semesters
+            .iter()
+            .map(|entry| {
+                if let Some(identifier) = entry
+                    .status
+                    .keys()
+                    .find(|identifier| !entry.modules.contains(identifier))
+                {
+                    return Err(LoadError::UnlistedStatus {
+                        identifier: identifier.clone(),
+                        semester: entry.number,
+                    });
+                }
+
+                let modules = entry
+                    .modules
+                    .iter()
+                    .map(|identifier| {
+                        catalog
+                            .find(identifier)
+                            .map(|module| match entry.status.get(identifier) {
+                                Some(status) => self.with_overrides(module).with_status(*status),
+                                None => self.with_overrides(module),
+                            })
+                            .ok_or_else(|| LoadError::UnknownModule {
+                                identifier: identifier.clone(),
+                                semester: entry.number,
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(Semester::new(
+                    entry.number,
+                    entry.semester_type.clone(),
+                    entry.degrees.clone(),
+                    entry.half_ects.min..entry.half_ects.max,
+                )
+                .with_modules(modules))
+            })
+            .collect()
+    }
+
+    /// Catalog modules no semester lists yet, with the overrides of the plan file applied
+    pub fn remaining_modules(&self, catalog: &Catalog) -> Vec<Module> {
+        catalog
+            .modules
+            .iter()
+            .filter(|module| {
+                !self
+                    .semesters
+                    .iter()
+                    .flat_map(|entry| &entry.modules)
+                    .any(|taken| *taken == module.identifier)
+            })
+            .map(|module| self.with_overrides(module))
+            .collect()
+    }
+
+    /// Applies the preference and target grade from the plan file
+    fn with_overrides(&self, module: &Module) -> Module {
+        let mut module = module.clone();
+        if let Some(preference) = self.preferences.get(&module.identifier) {
+            module = module.with_preference(*preference);
+        }
+        if let Some(target_grade) = self.target_grades.get(&module.identifier) {
+            module = module.with_target_grade(*target_grade);
+        }
+        if self.additional.contains(&module.identifier) {
+            module = module.with_degree(Degree::Additional);
+        }
+        module
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ModuleType, Speciality, SupplementarySubject};
+
+    fn catalog() -> Catalog {
+        Catalog {
+            specialities: vec![Speciality::new("Algorithms"), Speciality::new("Security")],
+            supplementary_subjects: vec![SupplementarySubject::new("Mathematics")],
+            modules: vec![],
+        }
+    }
+
+    fn plan_file(choices: &str) -> PlanFile {
+        toml::from_str(choices).unwrap()
+    }
+
+    #[test]
+    fn accepts_declared_choices() {
+        let plan_file = plan_file(
+            r#"
+            specialities = { pinned = ["Algorithms"], excluded = ["Security"] }
+            supplementary_subjects = { master = "Mathematics" }
+            "#,
+        );
+        assert!(plan_file.check_choices(&catalog()).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_specialities() {
+        let plan_file = plan_file(r#"specialities = { candidates = ["Algorithms", "Robotics"] }"#);
+        let robotics = Speciality::new("Robotics");
+        assert!(matches!(
+            plan_file.check_choices(&catalog()),
+            Err(LoadError::UnknownSpeciality { speciality }) if speciality == robotics
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_subjects() {
+        let plan_file = plan_file(r#"supplementary_subjects = { bachelor = "Physics" }"#);
+        let physics = SupplementarySubject::new("Physics");
+        assert!(matches!(
+            plan_file.check_choices(&catalog()),
+            Err(LoadError::UnknownSubject { subject }) if subject == physics
+        ));
+    }
+
+    #[test]
+    fn rejects_overrides_of_unknown_modules() {
+        let mut catalog = catalog();
+        catalog.modules = vec![Module::new("M-1", "Algo", ModuleType::Lab, 12, Degree::Bachelor)];
+        let checked = |overrides: &str| plan_file(overrides).check_choices(&catalog);
+
+        assert!(checked(r#"preferences = { "M-1" = "must" }"#).is_ok());
+        for (overrides, expected) in [
+            (r#"preferences = { "M-2" = "must" }"#, "preferences"),
+            (r#"target_grades = { "M-2" = 1.3 }"#, "target_grades"),
+            (r#"additional = ["M-2"]"#, "additional"),
+        ] {
+            assert!(matches!(
+                checked(overrides),
+                Err(LoadError::UnknownOverride { identifier, setting })
+                    if identifier == "M-2" && setting == expected
+            ));
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::catalog::Catalog;
+use crate::error::{read_toml, LoadError};
+use crate::model::{SemesterDegree, Speciality};
+
+/// ECTS range a block of a degree, like the supplementary subject, has to fall into
+#[derive(Clone, Debug, Hash, Deserialize)]
+pub struct EctsRange {
+    /// Least ECTS the block needs
+    pub min_ects: i32,
+    /// Most ECTS the block may count, further modules are additional
+    pub max_ects: i32,
+}
+
+/// Rules of an examination regulation (SPO). All values are in whole ECTS.
+#[derive(Clone, Debug, Hash, Deserialize)]
+pub struct Regulation {
+    /// How far the counted ECTS of a degree may exceed its minimum
+    pub ects_tolerance: i32,
+    /// ECTS every root module (Stammmodul) has
+    pub root_module_ects: i32,
+    pub bachelor: DegreeRegulation,
+    pub master: MasterRegulation,
+    pub speciality: SpecialityRegulation,
+}
+
+/// Rules both the bachelor and the master have
+#[derive(Clone, Debug, Hash, Deserialize)]
+pub struct DegreeRegulation {
+    /// Total of the degree, including the thesis
+    pub ects: i32,
+    /// Blocks that are not part of the catalog, like an internship
+    #[serde(default)]
+    pub other_ects: i32,
+    /// Number of root modules (Stammmodule) that have to count toward the degree
+    pub root_modules: i32,
+    /// No thesis is required if not given
+    pub thesis: Option<ThesisRegulation>,
+    /// Supplementary subject (Ergänzungsfach), not required if not given
+    pub supplementary: Option<EctsRange>,
+    /// Key qualifications (Schlüsselqualifikationen), not required if not given
+    pub key_qualifications: Option<EctsRange>,
+}
+
+/// Rules of the master, on top of the ones both degrees have
+#[derive(Clone, Debug, Hash, Deserialize)]
+pub struct MasterRegulation {
+    /// Rules shared with the bachelor, written inline in the `[master]` table
+    #[serde(flatten)]
+    pub common: DegreeRegulation,
+    /// Least ECTS of labs (Praktika)
+    pub lab_ects: i32,
+    /// Least ECTS of seminars
+    pub seminar_ects: i32,
+    /// Least ECTS of labs and seminars together
+    pub lab_seminar_ects: i32,
+    /// Lab and seminar ECTS below this cap are not counted toward the master sum
+    pub lab_seminar_cap_ects: i32,
+    /// Master ECTS that may be taken early while still enrolled in the bachelor, unlimited if
+    /// not given
+    pub max_early_ects: Option<i32>,
+}
+
+/// Rules for starting the thesis of a degree
+#[derive(Clone, Debug, Default, Hash, Deserialize)]
+pub struct ThesisRegulation {
+    /// ECTS of the degree that have to be passed before the thesis can be started, including
+    /// `other_ects`
+    #[serde(default)]
+    pub min_passed_ects: i32,
+}
+
+/// Rules for the specialities of the master
+#[derive(Clone, Debug, Hash, Deserialize)]
+pub struct SpecialityRegulation {
+    /// Number of specialities (Vertiefungsfächer) that have to be picked
+    pub count: usize,
+    /// Least ECTS every picked speciality needs
+    pub ects: i32,
+    /// Least ECTS every picked speciality needs without counting its root modules
+    pub ects_without_root: i32,
+    /// Specialities that need different ECTS without root modules than `ects_without_root`
+    #[serde(default)]
+    pub ects_without_root_exceptions: BTreeMap<Speciality, i32>,
+}
+
+impl Regulation {
+    /// Reads a regulation from a TOML file and rejects rules that make no sense
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Regulation, LoadError> {
+        let regulation: Regulation = read_toml(path.as_ref())?;
+        regulation.check()?;
+        Ok(regulation)
+    }
+
+    /// Rejects ECTS exceptions for specialities the catalog does not declare
+    pub fn check_catalog(&self, catalog: &Catalog) -> Result<(), LoadError> {
+        match self
+            .speciality
+            .ects_without_root_exceptions
+            .keys()
+            .find(|speciality| !catalog.specialities.contains(speciality))
+        {
+            Some(speciality) => Err(LoadError::UnknownException {
+                speciality: speciality.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Rejects rules no plan could ever satisfy or that make no sense
+    fn check(&self) -> Result<(), LoadError> {
+        if self.ects_tolerance < 0 {
+            return Err(LoadError::NegativeTolerance {
+                ects: self.ects_tolerance,
+            });
+        }
+        if self.speciality.count == 0 {
+            return Err(LoadError::NoSpecialities);
+        }
+        let degrees = [
+            (SemesterDegree::Bachelor, &self.bachelor),
+            (SemesterDegree::Master, &self.master.common),
+        ];
+        for (degree, regulation) in degrees {
+            let ranges = [
+                ("supplementary subject", &regulation.supplementary),
+                ("key qualifications", &regulation.key_qualifications),
+            ];
+            for (block, range) in ranges {
+                let Some(range) = range else {
+                    continue;
+                };
+                if range.min_ects > range.max_ects {
+                    return Err(LoadError::EmptyRange {
+                        degree: degree.clone(),
+                        block,
+                        min_ects: range.min_ects,
+                        max_ects: range.max_ects,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGULATION: &str = r#"
+        ects_tolerance = 4
+        root_module_ects = 6
+
+        [bachelor]
+        ects = 180
+        root_modules = 1
+        supplementary = { min_ects = 18, max_ects = 22 }
+
+        [master]
+        ects = 120
+        root_modules = 4
+        lab_ects = 6
+        seminar_ects = 3
+        lab_seminar_ects = 3
+        lab_seminar_cap_ects = 18
+        key_qualifications = { min_ects = 2, max_ects = 6 }
+
+        [speciality]
+        count = 2
+        ects = 15
+        ects_without_root = 10
+
+        [speciality.ects_without_root_exceptions]
+        Telematics = 8
+    "#;
+
+    fn regulation(replace: &str, with: &str) -> Regulation {
+        toml::from_str(&REGULATION.replace(replace, with)).unwrap()
+    }
+
+    #[test]
+    fn accepts_a_valid_regulation() {
+        assert!(regulation("", "").check().is_ok());
+    }
+
+    #[test]
+    fn rejects_a_negative_tolerance() {
+        let error = regulation("ects_tolerance = 4", "ects_tolerance = -1").check();
+        assert!(matches!(error, Err(LoadError::NegativeTolerance { ects: -1 })));
+    }
+
+    #[test]
+    fn rejects_no_specialities() {
+        let error = regulation("count = 2", "count = 0").check();
+        assert!(matches!(error, Err(LoadError::NoSpecialities)));
+    }
+
+    #[test]
+    fn rejects_an_empty_supplementary_range() {
+        let error = regulation("max_ects = 22", "max_ects = 12").check();
+        assert!(matches!(
+            error,
+            Err(LoadError::EmptyRange {
+                degree: SemesterDegree::Bachelor,
+                block: "supplementary subject",
+                min_ects: 18,
+                max_ects: 12,
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_exceptions_for_unknown_specialities() {
+        let catalog = Catalog {
+            specialities: vec![Speciality::new("Algorithms")],
+            supplementary_subjects: vec![],
+            modules: vec![],
+        };
+        let error = regulation("", "").check_catalog(&catalog);
+        let telematics = Speciality::new("Telematics");
+        assert!(matches!(
+            error,
+            Err(LoadError::UnknownException { speciality }) if speciality == telematics
+        ));
+    }
+
+    #[test]
+    fn rejects_an_empty_key_qualification_range() {
+        let error = regulation("min_ects = 2", "min_ects = 8").check();
+        assert!(matches!(
+            error,
+            Err(LoadError::EmptyRange {
+                degree: SemesterDegree::Master,
+                block: "key qualifications",
+                min_ects: 8,
+                max_ects: 6,
+            })
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::export::PlanExport;
+use crate::model::{Plan, PlanModuleView, Speciality};
+
+/// The specialities of a plan, sorted since their order does not matter
+pub type SpecialitySet = Vec<Option<Speciality>>;
+
+/// Solutions grouped by their specialities. Plans that only differ in when modules are taken are
+/// collapsed into one.
+pub struct SolutionGroups {
+    groups: BTreeMap<SpecialitySet, Vec<PlanModuleView>>,
+    solution_count: usize,
+}
+
+impl SolutionGroups {
+    pub fn new(solutions: &[Plan]) -> SolutionGroups {
+        let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for solution in solutions {
+            let mut specialties = solution.specialties().to_vec();
+            specialties.sort();
+            groups
+                .entry(specialties)
+                .or_default()
+                .push(PlanModuleView(solution.clone()));
+        }
+        // Keep the order the solver found the plans in, the preferred ones come first
+        for plans in groups.values_mut() {
+            let mut seen = HashSet::new();
+            let keep: Vec<_> = plans.iter().map(|plan| seen.insert(plan)).collect();
+            let mut keep = keep.into_iter();
+            plans.retain(|_| keep.next().unwrap_or(false));
+        }
+
+        SolutionGroups {
+            groups,
+            solution_count: solutions.len(),
+        }
+    }
+
+    /// Number of distinct plans over all groups
+    pub fn len(&self) -> usize {
+        self.groups.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn plans(&self) -> impl Iterator<Item = &Plan> {
+        self.groups.values().flatten().map(|plan| &plan.0)
+    }
+
+    pub fn render_text(&self) -> String {
+        let name = |specialty: &Option<Speciality>| {
+            specialty
+                .as_ref()
+                .map_or("unknown".to_string(), Speciality::to_string)
+        };
+        let mut text = String::new();
+        for (specialties, plans) in &self.groups {
+            let names: Vec<_> = specialties.iter().map(name).collect();
+            text += &format!(
+                "===== {}: {} plans =====\n\n",
+                names.join(" and "),
+                plans.len()
+            );
+            text += &plans
+                .iter()
+                .map(|plan| format!("{}", plan.0))
+                .collect::<Vec<_>>()
+                .join("\n\n\n\n\n");
+            text += "\n\n\n\n\n";
+        }
+        text += &format!("{}, {}", self.solution_count, self.len());
+        text
+    }
+
+    pub fn render_json(&self) -> serde_json::Result<String> {
+        let exports: Vec<_> = self.plans().map(PlanExport::from).collect();
+        serde_json::to_string_pretty(&exports)
+    }
+}
@@ -0,0 +1,432 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::catalog::Catalog;
+use crate::model::{
+    Degree, Module, Requirement, Semester, SemesterType, Speciality, SupplementarySubject,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    /// The solver would panic or can never find a plan
+    Error,
+    /// Probably a mistake in the data, but solving still works
+    Warning,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Diagnostic {
+    DuplicateIdentifier { identifier: String },
+    DanglingRequirement { module: String, requirement: String },
+    CyclicRequirements { chain: Vec<String> },
+    NeverOffered { module: String, semesters: Vec<SemesterType> },
+    StrayWhitespace { identifier: String },
+    NoSpecialities { module: String },
+    EctsSplitMismatch { module: String, split_half_ects: i32, half_ects: i32 },
+    UnknownSpeciality { module: String, speciality: Speciality },
+    UnknownSubject { module: String, subject: SupplementarySubject },
+    EmptyRequirementGroup { module: String, requirement: Requirement },
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Diagnostic::DuplicateIdentifier { .. }
+            | Diagnostic::DanglingRequirement { .. }
+            | Diagnostic::CyclicRequirements { .. }
+            | Diagnostic::EctsSplitMismatch { .. }
+            | Diagnostic::UnknownSpeciality { .. }
+            | Diagnostic::UnknownSubject { .. }
+            | Diagnostic::EmptyRequirementGroup { .. } => Severity::Error,
+            Diagnostic::NeverOffered { .. }
+            | Diagnostic::StrayWhitespace { .. }
+            | Diagnostic::NoSpecialities { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: ", self.severity())?;
+        match self {
+            Diagnostic::DuplicateIdentifier { identifier } => {
+                write!(f, "{:?} is used by more than one module", identifier)
+            }
+            Diagnostic::DanglingRequirement { module, requirement } => {
+                write!(f, "{} requires the unknown module {:?}", module, requirement)
+            }
+            Diagnostic::CyclicRequirements { chain } => {
+                write!(f, "Cyclic requirements {}", chain.join(" -> "))
+            }
+            Diagnostic::NeverOffered { module, semesters } => write!(
+                f,
+                "{} is only offered in {:?}, which is not part of the timeline",
+                module, semesters
+            ),
+            Diagnostic::StrayWhitespace { identifier } => {
+                write!(f, "Identifier {:?} has leading or trailing whitespace", identifier)
+            }
+            Diagnostic::EctsSplitMismatch { module, split_half_ects, half_ects } => write!(
+                f,
+                "{} splits {} half ECTS over its semesters but has {}",
+                module, split_half_ects, half_ects
+            ),
+            Diagnostic::UnknownSpeciality { module, speciality } => write!(
+                f,
+                "{} counts toward {}, which the catalog does not declare",
+                module, speciality
+            ),
+            Diagnostic::UnknownSubject { module, subject } => write!(
+                f,
+                "{} belongs to the supplementary subject {}, which the catalog does not declare",
+                module, subject
+            ),
+            Diagnostic::EmptyRequirementGroup { module, requirement } => write!(
+                f,
+                "{} has the requirement {}, which contains an empty any_of or all_of",
+                module, requirement
+            ),
+            Diagnostic::NoSpecialities { module } => write!(
+                f,
+                "{} is a master module without specialities and can never be used",
+                module
+            ),
+        }
+    }
+}
+
+/// Checks the catalog and the semester timeline for mistakes the solver can't report by itself
+pub fn validate(catalog: &Catalog, semesters: &[Semester]) -> Vec<Diagnostic> {
+    let modules = &catalog.modules;
+    let mut diagnostics = vec![];
+
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    for module in modules {
+        if !seen.insert(&module.identifier) && reported.insert(&module.identifier) {
+            diagnostics.push(Diagnostic::DuplicateIdentifier {
+                identifier: module.identifier.clone(),
+            });
+        }
+    }
+
+    let timeline_types: Vec<_> = semesters
+        .iter()
+        .map(|semester| &semester.semester_type)
+        .collect();
+    for module in modules {
+        let requirements: Vec<_> = module
+            .requirements
+            .iter()
+            .chain(&module.recommendations)
+            .flat_map(Requirement::identifiers)
+            .chain(module.corequisites.iter().map(|corequisite| &corequisite.module))
+            .collect();
+        let identifiers = std::iter::once(&module.identifier).chain(requirements.iter().copied());
+        for identifier in identifiers {
+            if identifier.trim() != identifier {
+                diagnostics.push(Diagnostic::StrayWhitespace {
+                    identifier: identifier.clone(),
+                });
+            }
+        }
+
+        let empty_groups = module
+            .requirements
+            .iter()
+            .chain(&module.recommendations)
+            .filter(|requirement| requirement.has_empty_group());
+        for requirement in empty_groups {
+            diagnostics.push(Diagnostic::EmptyRequirementGroup {
+                module: module.label(),
+                requirement: requirement.clone(),
+            });
+        }
+
+        for requirement in requirements {
+            if !seen.contains(requirement) {
+                diagnostics.push(Diagnostic::DanglingRequirement {
+                    module: module.label(),
+                    requirement: requirement.clone(),
+                });
+            }
+        }
+
+        if let Degree::Master(module_specialities) = &module.degree {
+            let unknown = module_specialities
+                .iter()
+                .filter(|speciality| !catalog.specialities.contains(speciality));
+            for speciality in unknown {
+                diagnostics.push(Diagnostic::UnknownSpeciality {
+                    module: module.label(),
+                    speciality: speciality.clone(),
+                });
+            }
+        }
+
+        if let Some(subject) = &module.subject {
+            if !catalog.supplementary_subjects.contains(subject) {
+                diagnostics.push(Diagnostic::UnknownSubject {
+                    module: module.label(),
+                    subject: subject.clone(),
+                });
+            }
+        }
+
+        if matches!(&module.degree, Degree::Master(specialities) if specialities.is_empty())
+            && module.has_speciality()
+        {
+            diagnostics.push(Diagnostic::NoSpecialities {
+                module: module.label(),
+            });
+        }
+
+        let split_half_ects: i32 = module.half_ects_per_semester().iter().sum();
+        if split_half_ects != module.half_ects {
+            diagnostics.push(Diagnostic::EctsSplitMismatch {
+                module: module.label(),
+                split_half_ects,
+                half_ects: module.half_ects,
+            });
+        }
+
+        let offered = module.semesters.contains(&SemesterType::Unknown)
+            || module
+                .semesters
+                .iter()
+                .any(|semester_type| timeline_types.contains(&semester_type));
+        if !offered {
+            diagnostics.push(Diagnostic::NeverOffered {
+                module: module.label(),
+                semesters: module.semesters.clone(),
+            });
+        }
+    }
+
+    for chain in requirement_cycles(modules) {
+        diagnostics.push(Diagnostic::CyclicRequirements { chain });
+    }
+
+    diagnostics.sort_by_key(Diagnostic::severity);
+    diagnostics
+}
+
+/// Finds every cycle in the requirement graph once, starting at its smallest identifier. Only
+/// requirements without alternatives count, since a cycle through an any-of can be avoided.
+fn requirement_cycles(modules: &[Module]) -> Vec<Vec<String>> {
+    let requirements: HashMap<_, _> = modules
+        .iter()
+        .map(|module| {
+            let mandatory: Vec<_> = module
+                .requirements
+                .iter()
+                .flat_map(Requirement::mandatory_identifiers)
+                .map(String::as_str)
+                .collect();
+            (module.identifier.as_str(), mandatory)
+        })
+        .collect();
+    let mut required_by: HashMap<_, Vec<_>> = HashMap::new();
+    for (identifier, mandatory) in &requirements {
+        for requirement in mandatory {
+            required_by.entry(*requirement).or_default().push(*identifier);
+        }
+    }
+
+    let mut identifiers: Vec<_> = requirements.keys().copied().collect();
+    identifiers.sort();
+    let mut cycles = vec![];
+    for start in identifiers {
+        // Cycles through smaller identifiers were found from those already
+        let mut leads_back = HashSet::new();
+        let mut pending = vec![start];
+        while let Some(identifier) = pending.pop() {
+            for dependent in required_by.get(identifier).into_iter().flatten().copied() {
+                if dependent > start && leads_back.insert(dependent) {
+                    pending.push(dependent);
+                }
+            }
+        }
+
+        let mut path = vec![start];
+        visit(start, &requirements, &leads_back, &mut path, &mut cycles);
+    }
+
+    cycles.sort();
+    cycles.dedup();
+    cycles
+}
+
+/// Extends `path` by every requirement that leads back to its start without repeating a module
+fn visit<'a>(
+    identifier: &'a str,
+    requirements: &HashMap<&'a str, Vec<&'a str>>,
+    leads_back: &HashSet<&'a str>,
+    path: &mut Vec<&'a str>,
+    cycles: &mut Vec<Vec<String>>,
+) {
+    for requirement in requirements.get(identifier).into_iter().flatten().copied() {
+        if requirement == path[0] {
+            let mut cycle: Vec<_> = path.iter().map(|entry| entry.to_string()).collect();
+            cycle.push(requirement.to_string());
+            cycles.push(cycle);
+        } else if leads_back.contains(requirement) && !path.contains(&requirement) {
+            path.push(requirement);
+            visit(requirement, requirements, leads_back, path, cycles);
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Corequisite, ModuleType, SemesterDegree};
+
+    fn module(identifier: &str, requirements: &[&str]) -> Module {
+        let requirements = requirements
+            .iter()
+            .map(|requirement| Requirement::Module(requirement.to_string()))
+            .collect();
+        Module::new(identifier, identifier, ModuleType::Lab, 10, Degree::Bachelor)
+            .with_requirements(requirements)
+    }
+
+    fn chain(identifiers: &[&str]) -> Vec<String> {
+        identifiers.iter().map(|identifier| identifier.to_string()).collect()
+    }
+
+    fn catalog(modules: Vec<Module>, specialities: Vec<Speciality>) -> Catalog {
+        Catalog {
+            specialities,
+            supplementary_subjects: vec![SupplementarySubject::new("Mathematics")],
+            modules,
+        }
+    }
+
+    fn semesters() -> Vec<Semester> {
+        vec![Semester::new(1, SemesterType::Winter, vec![SemesterDegree::Bachelor], 0..60)]
+    }
+
+    #[test]
+    fn finds_no_cycles_in_a_chain() {
+        let modules = [module("A", &["B"]), module("B", &["C"]), module("C", &[])];
+        assert!(requirement_cycles(&modules).is_empty());
+    }
+
+    #[test]
+    fn reports_a_cycle_once_from_its_smallest_identifier() {
+        let modules = [module("B", &["C"]), module("C", &["A"]), module("A", &["B"])];
+        assert_eq!(requirement_cycles(&modules), vec![chain(&["A", "B", "C", "A"])]);
+    }
+
+    #[test]
+    fn finds_cycles_sharing_modules() {
+        let modules = [module("A", &["B", "C"]), module("B", &["C"]), module("C", &["A"])];
+        assert_eq!(
+            requirement_cycles(&modules),
+            vec![chain(&["A", "B", "C", "A"]), chain(&["A", "C", "A"])]
+        );
+    }
+
+    #[test]
+    fn ignores_cycles_through_alternatives() {
+        let alternatives = Requirement::AnyOf {
+            any_of: vec![
+                Requirement::Module("B".to_string()),
+                Requirement::Module("C".to_string()),
+            ],
+        };
+        let modules = [
+            module("A", &[]).with_requirements(vec![alternatives]),
+            module("B", &["A"]),
+            module("C", &[]),
+        ];
+        assert!(requirement_cycles(&modules).is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_identifiers() {
+        let modules = vec![
+            module("A", &["X"]),
+            module("B", &[])
+                .with_recommendations(vec![Requirement::Module("Y".to_string())])
+                .with_corequisites(vec![Corequisite {
+                    module: "Z".to_string(),
+                    timing: Default::default(),
+                }]),
+        ];
+        let requirements: Vec<_> = validate(&catalog(modules, vec![]), &semesters())
+            .into_iter()
+            .filter_map(|diagnostic| match diagnostic {
+                Diagnostic::DanglingRequirement { requirement, .. } => Some(requirement),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(requirements, chain(&["X", "Y", "Z"]));
+    }
+
+    #[test]
+    fn reports_unknown_specialities() {
+        let specialities = vec![Speciality::new("Algorithms")];
+        let modules = vec![module("A", &[]).with_degree(Degree::Master(vec![
+            Speciality::new("Algorithms"),
+            Speciality::new("Robotics"),
+        ]))];
+        let label = modules[0].label();
+        let diagnostics = validate(&catalog(modules, specialities), &semesters());
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::UnknownSpeciality {
+                module: label,
+                speciality: Speciality::new("Robotics"),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_subjects() {
+        let modules = vec![
+            module("A", &[]).with_subject(SupplementarySubject::new("Mathematics")),
+            module("B", &[]).with_subject(SupplementarySubject::new("Physics")),
+        ];
+        let label = modules[1].label();
+        let diagnostics = validate(&catalog(modules, vec![]), &semesters());
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::UnknownSubject {
+                module: label,
+                subject: SupplementarySubject::new("Physics"),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_empty_requirement_groups() {
+        let empty = Requirement::AllOf {
+            all_of: vec![
+                Requirement::Module("B".to_string()),
+                Requirement::AnyOf { any_of: vec![] },
+            ],
+        };
+        let modules = vec![
+            module("A", &[]).with_requirements(vec![empty.clone()]),
+            module("B", &[]).with_requirements(vec![Requirement::AllOf { all_of: vec![] }]),
+        ];
+        let labels = (modules[0].label(), modules[1].label());
+        let diagnostics = validate(&catalog(modules, vec![]), &semesters());
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::EmptyRequirementGroup {
+                    module: labels.0,
+                    requirement: empty,
+                },
+                Diagnostic::EmptyRequirementGroup {
+                    module: labels.1,
+                    requirement: Requirement::AllOf { all_of: vec![] },
+                },
+            ]
+        );
+    }
+}
@@ -1,10 +1,79 @@
-use std::fmt::format;
-use strum::IntoEnumIterator;
-use z3::{ast, Context, FuncDecl, Solver, Sort};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use z3::{ast, Context, FuncDecl, Optimize, Params, Solver, Sort};
 use z3::ast::{Ast, Bool, Datatype};
+use crate::explanation::Reason;
 use crate::model::Degree::Bachelor;
-use crate::model::{Degree, Module, Speciality};
+use crate::model::{Degree, Module, Preference, Speciality};
+
+pub trait TrackedAssertions<'ctx> {
+    fn assert_tracked(&self, constraint: &Bool<'ctx>, reason: Reason);
+}
+
+/// A solver that remembers the reason behind every tracking literal, so unsat cores can be mapped
+/// back to them
+pub struct TrackingSolver<'ctx> {
+    solver: Solver<'ctx>,
+    reasons: RefCell<HashMap<String, Reason>>,
+}
+
+impl<'ctx> TrackingSolver<'ctx> {
+    pub fn new(context: &'ctx Context) -> TrackingSolver<'ctx> {
+        let solver = Solver::new(context);
+        let mut params = Params::new(context);
+        params.set_bool("core.minimize", true);
+        solver.set_params(&params);
+
+        TrackingSolver {
+            solver,
+            reasons: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn unsat_reasons(&self) -> Vec<Reason> {
+        let reasons = self.reasons.borrow();
+        self.solver
+            .get_unsat_core()
+            .iter()
+            .filter_map(|tracker| reasons.get(tracker.to_string().trim_matches('|')))
+            .cloned()
+            .collect()
+    }
+}
+
+impl<'ctx> Deref for TrackingSolver<'ctx> {
+    type Target = Solver<'ctx>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.solver
+    }
+}
+
+impl<'ctx> TrackedAssertions<'ctx> for TrackingSolver<'ctx> {
+    fn assert_tracked(&self, constraint: &Bool<'ctx>, reason: Reason) {
+        // The debug representation is unique for every distinct reason
+        let name = format!("{:?}", reason);
+        let tracker = Bool::new_const(self.solver.get_context(), name.clone());
+        self.reasons.borrow_mut().insert(name, reason);
+        self.solver.assert_and_track(constraint, &tracker)
+    }
+}
 
+// Optimize has no unsat cores, so the reasons are dropped
+impl<'ctx> TrackedAssertions<'ctx> for Optimize<'ctx> {
+    fn assert_tracked(&self, constraint: &Bool<'ctx>, _reason: Reason) {
+        self.assert(constraint)
+    }
+}
+
+/// The sort of what a module counts toward, with the testers modules are constrained by
+pub struct DegreeSort<'a, 'ctx> {
+    pub sort: &'a Sort<'ctx>,
+    pub is_bachelor: &'a FuncDecl<'ctx>,
+    pub is_additional: &'a FuncDecl<'ctx>,
+}
 
 pub struct Z3Module<'ctx> {
     pub used: Bool<'ctx>,
@@ -12,35 +81,50 @@ pub struct Z3Module<'ctx> {
     pub degree: Datatype<'ctx>,
     pub associated_specialty: Datatype<'ctx>,
     pub ects: ast::Int<'ctx>,
-    pub identifier: &'static str,
+    /// Half ECTS counted in each semester from `semester` on
+    pub ects_split: Vec<i32>,
+    pub identifier: String,
+    pub label: String,
+    pub preference: Preference,
+    pub exclusion_groups: Vec<String>,
 }
 
 impl<'ctx> Z3Module<'ctx> {
-    pub fn from_module(context: &'ctx Context, module: &Module, degree_sort: &'ctx Sort, bachelor_tester: &FuncDecl, specialty_sort: &'ctx Sort, specialty_values: &[Datatype], solver: &Solver) -> Z3Module<'ctx> {
+    pub fn from_module(context: &'ctx Context, module: &Module, degree_sort: &DegreeSort<'_, 'ctx>, specialty_sort: &Sort<'ctx>, specialty_values: &[(Speciality, Datatype<'ctx>)], solver: &impl TrackedAssertions<'ctx>) -> Z3Module<'ctx> {
         let used = Bool::new_const(context, format!("used_{}_{}", module.name, module.identifier));
         let semester = ast::Int::new_const(context, format!("semester_{}_{}", module.name, module.identifier));
         let ects = ast::Int::from_i64(context, module.half_ects as i64);
         let specialty = Datatype::new_const(context, format!("specialty_{}_{}", module.name, module.identifier), specialty_sort);
-        if let Degree::Master(specialties) = &module.degree {
-            let specialty_values: Vec<_> = Speciality::iter().enumerate()
-                .filter(|(_, entry)| specialties.contains(entry))
-                .map(|(index, _)| &specialty_values[index])
+        let specialties = match &module.degree {
+            Degree::Master(specialties) if module.has_speciality() => Some(specialties),
+            _ => None,
+        };
+        if let Some(specialties) = specialties {
+            let specialty_values: Vec<_> = specialty_values.iter()
+                .filter(|(entry, _)| specialties.contains(entry))
+                .map(|(_, value)| value)
                 .collect();
             
             let matches_specialty = specialty_values.iter().map(|specialty_value| specialty_value._eq(&specialty)).collect::<Vec<_>>();
             let matches_specialty: Vec<_> = matches_specialty.iter().collect();
             let matches_any = Bool::or(context, matches_specialty.as_slice());
-            solver.assert_and_track(&matches_any, &Bool::new_const(context, format!("{}_{} must match its specialties", module.name, module.identifier)))
+            // Without any speciality the module can't be used, but that alone is satisfiable
+            solver.assert_tracked(&used.implies(&matches_any), Reason::ModuleSpecialities { module: module.label() })
         }
 
-        let degree = Datatype::new_const(context, format!("degree_{}_{}", module.name, module.identifier), degree_sort);
+        let degree = Datatype::new_const(context, format!("degree_{}_{}", module.name, module.identifier), degree_sort.sort);
+        let is_additional = degree_sort.is_additional.apply(&[&degree]).as_bool().unwrap();
         if module.degree == Bachelor {
-            let is_bachelor = bachelor_tester.apply(&[&degree]).as_bool().unwrap();
-            solver.assert_and_track(&is_bachelor, &Bool::new_const(context, format!("is_bachelor_{}_{}", module.name, module.identifier)));
+            let is_bachelor = degree_sort.is_bachelor.apply(&[&degree]).as_bool().unwrap();
+            solver.assert_tracked(&(is_bachelor | &is_additional), Reason::BachelorModule { module: module.label() });
+        } else if module.degree == Degree::Additional {
+            solver.assert_tracked(&is_additional, Reason::AdditionalModule { module: module.label() });
         }
         
-        if module.force {
-            solver.assert_and_track(&used, &Bool::new_const(context, format!("Module {}_{} was forced", module.name, module.identifier)))
+        match module.preference {
+            Preference::Must => solver.assert_tracked(&used, Reason::Forced { module: module.label() }),
+            Preference::Never => solver.assert_tracked(&!&used, Reason::Excluded { module: module.label() }),
+            Preference::Want | Preference::Neutral | Preference::Avoid => {}
         }
 
         Z3Module {
@@ -49,7 +133,39 @@ impl<'ctx> Z3Module<'ctx> {
             degree,
             associated_specialty: specialty,
             ects,
-            identifier: module.identifier,
+            ects_split: module.half_ects_per_semester(),
+            identifier: module.identifier.clone(),
+            label: module.label(),
+            preference: module.preference,
+            exclusion_groups: module.exclusion_groups.clone(),
         }
     }
-}
\ No newline at end of file
+
+    /// The last semester the module is taken in
+    pub fn end(&self) -> ast::Int<'ctx> {
+        let context = self.semester.get_ctx();
+        &self.semester + ast::Int::from_i64(context, self.ects_split.len() as i64 - 1)
+    }
+
+    /// Whether the module is taken in the semester with the given index, ignoring `used`
+    pub fn covers(&self, semester_index: usize) -> Bool<'ctx> {
+        let context = self.semester.get_ctx();
+        let index = ast::Int::from_i64(context, semester_index as i64);
+        self.semester.le(&index) & self.end().ge(&index)
+    }
+
+    /// Half ECTS counted in the semester with the given index, if the module is used
+    pub fn ects_in(&self, semester_index: usize) -> ast::Int<'ctx> {
+        let context = self.semester.get_ctx();
+        let zero = ast::Int::from_i64(context, 0);
+        self.ects_split
+            .iter()
+            .enumerate()
+            .filter(|(offset, _)| *offset <= semester_index)
+            .fold(zero.clone(), |sum, (offset, half_ects)| {
+                let start = ast::Int::from_i64(context, (semester_index - offset) as i64);
+                let half_ects = ast::Int::from_i64(context, *half_ects as i64);
+                sum + (&self.used & self.semester._eq(&start)).ite(&half_ects, &zero)
+            })
+    }
+}